@@ -109,8 +109,8 @@ export class StellarService {
 
   // ── Mint / Burn ──────────────────────────────────────────────────────────
 
-  async mintPoints(toPublicKey: string, amount: number): Promise<string> {
-    console.log(`[mint] Iniciando mint de ${amount} pts → ${toPublicKey}`);
+  async mintPoints(toPublicKey: string, amount: number, category = 'general'): Promise<string> {
+    console.log(`[mint] Iniciando mint de ${amount} pts (${category}) → ${toPublicKey}`);
     const contract = this.getPointsContract();
     const adminKeypair = this.getAdminKeypair();
 
@@ -122,8 +122,10 @@ export class StellarService {
       .addOperation(
         contract.call(
           'mint',
+          new Address(adminKeypair.publicKey()).toScVal(),
           new Address(toPublicKey).toScVal(),
           nativeToScVal(BigInt(amount), { type: 'i128' }),
+          nativeToScVal(category, { type: 'symbol' }),
         ),
       )
       .setTimeout(30)
@@ -160,6 +162,7 @@ export class StellarService {
       .addOperation(
         contract.call(
           'clawback',
+          new Address(adminKeypair.publicKey()).toScVal(),
           new Address(fromPublicKey).toScVal(),
           nativeToScVal(BigInt(amount), { type: 'i128' }),
         ),
@@ -212,6 +215,7 @@ export class StellarService {
       .addOperation(
         contract.call(
           'issue_badge',
+          new Address(adminKeypair.publicKey()).toScVal(),
           new Address(studentPublicKey).toScVal(),
           nativeToScVal(BigInt(activityId), { type: 'u64' }),
           nativeToScVal(activityTitle, { type: 'string' }),
@@ -261,6 +265,7 @@ export class StellarService {
       .addOperation(
         contract.call(
           'record_redemption',
+          new Address(adminKeypair.publicKey()).toScVal(),
           new Address(studentPublicKey).toScVal(),
           nativeToScVal(rewardName, { type: 'string' }),
           nativeToScVal(BigInt(pointsSpent), { type: 'i128' }),
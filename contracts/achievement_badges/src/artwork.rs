@@ -0,0 +1,176 @@
+//! Arte SVG on-chain de los badges.
+//!
+//! Cada clase puede tener una plantilla SVG con los marcadores `{{title}}`,
+//! `{{student}}` (dirección abreviada), `{{date}}` (fecha de emisión, UTC) y
+//! `{{badge_id}}`. `token_uri` la completa con los datos del badge y la retorna como
+//! URI `data:image/svg+xml;base64,...`, así el badge se sigue viendo aunque el host de
+//! `image_uri` deje de responder.
+use crate::BadgeData;
+use soroban_sdk::{Env, String};
+
+/// Tamaño máximo de una plantilla, en bytes.
+pub const MAX_TEMPLATE_LEN: u32 = 4_096;
+
+/// Tamaño máximo del SVG ya completado, en bytes.
+const MAX_SVG_LEN: usize = 8_192;
+
+const URI_PREFIX: &[u8] = b"data:image/svg+xml;base64,";
+
+const MAX_URI_LEN: usize = URI_PREFIX.len() + MAX_SVG_LEN.div_ceil(3) * 4;
+
+/// Largo máximo de una dirección en strkey (cuentas muxed `M...`).
+const MAX_STRKEY_LEN: usize = 69;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Completa `template` con los datos de `badge` y retorna la URI `data:`. `None` si el
+/// SVG resultante supera `MAX_SVG_LEN`.
+pub fn render(env: &Env, template: &String, badge: &BadgeData) -> Option<String> {
+    let mut template_buf = [0u8; MAX_TEMPLATE_LEN as usize];
+    let template = read(template, &mut template_buf)?;
+
+    let mut svg = Buf::<MAX_SVG_LEN>::new();
+    let mut i = 0;
+    while i < template.len() {
+        let Some((name, next)) = placeholder(template, i) else {
+            svg.push(template[i])?;
+            i += 1;
+            continue;
+        };
+        match name {
+            b"title" => {
+                let mut title_buf = [0u8; MAX_SVG_LEN];
+                svg.push_escaped(read(&badge.activity_title, &mut title_buf)?)?;
+            }
+            b"student" => {
+                let mut strkey_buf = [0u8; MAX_STRKEY_LEN];
+                let strkey = read(&badge.student.to_string(), &mut strkey_buf)?;
+                svg.extend(&strkey[..4])?;
+                svg.extend("…".as_bytes())?;
+                svg.extend(&strkey[strkey.len() - 4..])?;
+            }
+            b"date" => svg.push_date(badge.issued_at)?,
+            b"badge_id" => svg.push_u64(badge.badge_id)?,
+            // Marcador desconocido: se deja tal cual.
+            _ => svg.extend(&template[i..next])?,
+        }
+        i = next;
+    }
+
+    let mut uri = Buf::<MAX_URI_LEN>::new();
+    uri.extend(URI_PREFIX)?;
+    uri.push_base64(svg.as_slice())?;
+    Some(String::from_bytes(env, uri.as_slice()))
+}
+
+/// Si en `template[i..]` empieza un marcador `{{nombre}}`, retorna el nombre y la
+/// posición siguiente al marcador.
+fn placeholder(template: &[u8], i: usize) -> Option<(&[u8], usize)> {
+    let rest = template[i..].strip_prefix(b"{{")?;
+    let end = rest.windows(2).take(16).position(|w| w == b"}}")?;
+    Some((&rest[..end], i + 2 + end + 2))
+}
+
+/// Copia `s` a `buf` y retorna la parte usada. `None` si no cabe.
+fn read<'a>(s: &String, buf: &'a mut [u8]) -> Option<&'a [u8]> {
+    let len = s.len() as usize;
+    let buf = buf.get_mut(..len)?;
+    s.copy_into_slice(buf);
+    Some(buf)
+}
+
+/// Buffer de tamaño fijo: el contrato es `no_std` y no tiene allocator.
+struct Buf<const N: usize> {
+    data: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Buf<N> {
+    fn new() -> Self {
+        Buf { data: [0; N], len: 0 }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    fn push(&mut self, byte: u8) -> Option<()> {
+        *self.data.get_mut(self.len)? = byte;
+        self.len += 1;
+        Some(())
+    }
+
+    fn extend(&mut self, bytes: &[u8]) -> Option<()> {
+        self.data
+            .get_mut(self.len..self.len + bytes.len())?
+            .copy_from_slice(bytes);
+        self.len += bytes.len();
+        Some(())
+    }
+
+    /// Agrega texto escapado para XML.
+    fn push_escaped(&mut self, text: &[u8]) -> Option<()> {
+        for &byte in text {
+            match byte {
+                b'&' => self.extend(b"&amp;")?,
+                b'<' => self.extend(b"&lt;")?,
+                b'>' => self.extend(b"&gt;")?,
+                b'"' => self.extend(b"&quot;")?,
+                b'\'' => self.extend(b"&apos;")?,
+                _ => self.push(byte)?,
+            }
+        }
+        Some(())
+    }
+
+    fn push_u64(&mut self, mut n: u64) -> Option<()> {
+        let mut digits = [0u8; 20];
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        self.extend(&digits[i..])
+    }
+
+    /// Agrega la fecha UTC (`AAAA-MM-DD`) de un timestamp Unix.
+    fn push_date(&mut self, timestamp: u64) -> Option<()> {
+        // Días desde 1970-01-01 → fecha civil (algoritmo de H. Hinnant).
+        let z = timestamp / 86_400 + 719_468;
+        let era = z / 146_097;
+        let doe = z % 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + u64::from(month <= 2);
+
+        self.push_u64(year)?;
+        for part in [month, day] {
+            self.push(b'-')?;
+            self.push(b'0' + (part / 10) as u8)?;
+            self.push(b'0' + (part % 10) as u8)?;
+        }
+        Some(())
+    }
+
+    fn push_base64(&mut self, bytes: &[u8]) -> Option<()> {
+        for chunk in bytes.chunks(3) {
+            let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+            let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+            for k in 0..4 {
+                if k <= chunk.len() {
+                    self.push(BASE64[(n >> (18 - 6 * k)) as usize & 0x3f])?;
+                } else {
+                    self.push(b'=')?;
+                }
+            }
+        }
+        Some(())
+    }
+}
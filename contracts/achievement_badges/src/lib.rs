@@ -1,7 +1,16 @@
 #![no_std]
+// Las entrypoints reciben los campos del badge como argumentos sueltos, igual que los
+// ve el backend al invocarlas.
+#![allow(clippy::too_many_arguments)]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String, Vec,
+    contract, contractclient, contracterror, contractimpl, contracttype, symbol_short,
+    xdr::ToXdr, Address, Bytes, BytesN, Env, String, Symbol, Vec,
 };
+use xpuni_access::{AccessControl, Pausable, Role};
+use xpuni_common::{Index, Registry, StorageMode, MAX_PAGE_SIZE};
+use xpuni_timelock::{QueuedOperation, TimelockAction, TimelockHook, Timelocked};
+
+mod artwork;
 
 // ── Data structures ───────────────────────────────────────────────────────────
 
@@ -9,6 +18,9 @@ use soroban_sdk::{
 #[derive(Clone)]
 pub struct BadgeData {
     pub badge_id: u64,
+    /// Clase de la que se emitió el badge, si la hay. En ese caso título, imagen y
+    /// puntos vienen de la clase.
+    pub class_id: Option<u64>,
     pub student: Address,
     pub activity_id: u64,
     pub activity_title: String,
@@ -21,35 +33,391 @@ pub struct BadgeData {
     pub description_hash: BytesN<32>,
 }
 
+/// Plantilla compartida por los badges idénticos: los datos que antes se repetían en
+/// cada `issue_badge` más el hash de los criterios de obtención.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeClass {
+    pub class_id: u64,
+    pub title: String,
+    pub image_uri: String,
+    pub criteria_hash: BytesN<32>,
+    pub default_points: i128,
+    /// Una clase desactivada ya no emite badges; los emitidos siguen vigentes.
+    pub active: bool,
+    /// Si los badges de la clase que emite un revisor quedan como oferta hasta que el
+    /// estudiante los acepte.
+    pub requires_acceptance: bool,
+}
+
+/// Badge emitido desde una clase: solo guarda lo propio de la instancia.
 #[contracttype]
 #[derive(Clone)]
-pub enum DataKey {
-    Admin,
-    BadgeCount,
+struct ClassBadge {
+    class_id: u64,
+    student: Address,
+    activity_id: u64,
+    reviewer: Address,
+    issued_at: u64,
+    description_hash: BytesN<32>,
+}
+
+/// Campos descriptivos de un badge emitido con `issue_badge`. En modo `Compact` solo se
+/// guarda su hash y se publican una vez en el evento `content`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeContent {
+    pub activity_title: String,
+    pub image_uri: String,
+}
+
+/// Badge emitido con `issue_badge` en modo `Compact`: en vez de título e imagen guarda
+/// el hash de su `BadgeContent`.
+#[contracttype]
+#[derive(Clone)]
+struct CompactBadge {
+    student: Address,
+    activity_id: u64,
+    reviewer: Address,
+    points_awarded: i128,
+    issued_at: u64,
+    description_hash: BytesN<32>,
+    content_hash: BytesN<32>,
+}
+
+/// Forma en que se guarda cada badge en el registro.
+#[contracttype]
+#[derive(Clone)]
+enum StoredBadge {
+    Full(BadgeData),
+    Class(ClassBadge),
+    Compact(CompactBadge),
+}
+
+/// Lote de badges de una clase publicado como raíz de Merkle. Cada estudiante reclama
+/// su propio badge con `claim`, presentando su hoja y la prueba de inclusión.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Batch {
+    pub batch_id: u64,
+    pub merkle_root: BytesN<32>,
+    pub class_id: u64,
+    /// Quién publicó el lote; queda como revisor de los badges reclamados.
+    pub published_by: Address,
+    pub published_at: u64,
+}
+
+/// Hoja de un lote. Su hash es el SHA-256 de su XDR (`ScVal` mapa), y cada nodo
+/// interno es el SHA-256 de sus dos hijos concatenados en orden ascendente.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchLeaf {
+    pub student: Address,
+    pub activity_id: u64,
+    pub description_hash: BytesN<32>,
+}
+
+/// Requisito de una ruta de aprendizaje: un badge de cierta clase o actividad.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Requirement {
+    Class(u64),
+    Activity(u64),
+}
+
+/// Ruta de aprendizaje: al cumplirla, el estudiante recibe automáticamente un badge de
+/// `meta_class_id`.
+///
+/// Con `threshold` en 0 hace falta al menos un badge por cada requisito; si no, basta
+/// con `threshold` badges que cumplan alguno (p. ej. 5 badges de la clase "Taller").
+/// Solo cuentan los badges entregados después de crear la ruta.
+///
+/// El avance es histórico: revocar un badge que ya contó no lo descuenta ni retira el
+/// meta-badge. Si la revocación invalida la ruta, el meta-badge se revoca aparte.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LearningPath {
+    pub path_id: u64,
+    pub meta_class_id: u64,
+    pub requirements: Vec<Requirement>,
+    pub threshold: u32,
+}
+
+/// Avance de un estudiante en una ruta (ver `path_progress`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathProgress {
+    pub path_id: u64,
+    /// Badges entregados que cumplen algún requisito.
+    pub matched_badges: u32,
+    /// Requisitos cumplidos, en orden de cumplimiento.
+    pub satisfied: Vec<Requirement>,
+    /// Badges (con `threshold`) o requisitos (sin él) necesarios para completarla.
+    pub needed: u32,
+    pub completed: bool,
+    /// Meta-badge emitido al completarla.
+    pub meta_badge_id: Option<u64>,
+}
+
+/// Objeto de un endoso: un badge concreto (un `AchievementCredential` en Open Badges)
+/// o una clase entera (un `Achievement`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EndorsementTarget {
     Badge(u64),
-    StudentBadges(Address),
+    Class(u64),
+}
+
+/// Endoso público de un tercero, según el concepto `EndorsementCredential` de Open
+/// Badges 3.0: `endorser` es el emisor y `comment_hash` ancla el `endorsementComment`,
+/// que vive fuera de la cadena.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Endorsement {
+    pub endorsement_id: u64,
+    pub target: EndorsementTarget,
+    pub endorser: Address,
+    pub comment_hash: BytesN<32>,
+    pub endorsed_at: u64,
+    /// El endosante lo retiró.
+    pub revoked: bool,
+}
+
+/// Premio a un equipo: un badge por miembro, vinculados por el team_id, con los puntos
+/// repartidos según `shares`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TeamAward {
+    pub team_id: u64,
+    pub activity_id: u64,
+    pub members: Vec<Address>,
+    pub shares: Vec<u32>,
+    pub total_points: i128,
+    /// Badge de cada miembro, en el orden de `members`.
+    pub badge_ids: Vec<u64>,
+    pub issued_at: u64,
+}
+
+/// Filtros de `query_badges`. Los badges deben haberse emitido en `[from, to)`
+/// (timestamps Unix) y, si se indican, ser de `activity_id` y de `reviewer`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeQuery {
+    pub activity_id: Option<u64>,
+    pub reviewer: Option<Address>,
+    pub from: u64,
+    pub to: u64,
+}
+
+/// Punto desde el que sigue una consulta de `query_badges`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryCursor {
+    /// Día en curso (`timestamp / 86_400`); solo cuenta si la consulta recorre días.
+    pub day: u64,
+    /// Posición dentro del índice en curso.
+    pub position: u32,
+}
+
+/// Revocación de un badge. Se guarda aparte para que `BadgeData` no cambie de forma.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Revocation {
+    pub revoked_by: Address,
+    pub revoked_at: u64,
+    /// Hash del motivo (p. ej. el informe de plagio), que vive fuera de la cadena.
+    pub reason_hash: BytesN<32>,
+    /// Puntos retirados del estudiante en `SchoolPoints` al revocar.
+    pub points_clawed_back: i128,
+}
+
+/// Resultado de `verify_badge`: todo lo que un empleador necesita para confiar en un
+/// badge con una sola lectura.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BadgeStatus {
+    pub exists: bool,
+    /// Dueño actual del badge, si existe.
+    pub owner: Option<Address>,
+    /// Si el dueño es el estudiante consultado.
+    pub owned_by_student: bool,
+    pub revoked: bool,
+    /// Si es una oferta que el estudiante todavía no aceptó.
+    pub pending: bool,
+    /// Si pasó su `valid_until`.
+    pub expired: bool,
+}
+
+/// Una renovación de un badge con vencimiento (ver `renew_badge`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Renewal {
+    pub renewed_by: Address,
+    pub renewed_at: u64,
+    pub previous_valid_until: Option<u64>,
+    pub valid_until: u64,
+}
+
+/// Errores tipados de la interfaz no fungible.
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum BadgeError {
+    /// Los badges quedan ligados al estudiante: no se transfieren ni se aprueban.
+    Soulbound = 1,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    /// Nombre de la colección (interfaz no fungible).
+    Name,
+    /// Símbolo de la colección (interfaz no fungible).
+    Symbol,
+    /// Contrato `SchoolPoints` donde se acreditan los puntos de los badges.
+    PointsContract,
+    /// badge_id → Revocation
+    Revocation(u64),
+    /// class_id → BadgeClass
+    Class(u64),
+    /// batch_id → Batch
+    Batch(u64),
+    /// (batch_id, hash de hoja) → badge_id emitido al reclamarla
+    Claimed(u64, BytesN<32>),
+    /// Revisor → activity IDs en los que puede emitir. Sin entrada, cualquiera.
+    ReviewerScope(Address),
+    /// badge_id de una oferta pendiente → timestamp en que vence
+    Offer(u64),
+    /// path_id → LearningPath
+    Path(u64),
+    /// Requisito → path IDs que lo incluyen
+    PathsFor(Requirement),
+    /// (estudiante, path_id) → PathProgress
+    Progress(Address, u64),
+    /// class_id → path_id que hay que completar antes de recibir badges de la clase
+    ClassPrereq(u64),
+    /// class_id → es el meta-badge de alguna ruta (no admite prerequisito)
+    MetaClass(u64),
+    /// (estudiante, activity_id) → último badge emitido para esa actividad
+    BadgeFor(Address, u64),
+    /// activity_id → si admite más de un badge por estudiante
+    Repeatable(u64),
+    /// class_id → segundos de validez de los badges que emite
+    ClassValidity(u64),
+    /// badge_id → timestamp hasta el que es válido
+    ValidUntil(u64),
+    /// badge_id → renovaciones, en orden
+    Renewals(u64),
+    /// badge_id → `valid_until` para el que ya se publicó el evento `expired`
+    ExpiredAt(u64),
+    /// (objeto, endosante) → endoso vigente
+    Endorsed(EndorsementTarget, Address),
+    /// class_id → plantilla SVG del arte on-chain de sus badges
+    ClassArtwork(u64),
+    /// Estudiante → badge IDs destacados en su vitrina, en orden
+    Featured(Address),
+    /// badge_id → oculto de la vitrina de su dueño
+    Hidden(u64),
+    /// badge_id → SHA-256 del `activity_title` borrado
+    Redacted(u64),
+    /// badge_id → team_id del premio de equipo al que pertenece
+    TeamOf(u64),
+}
+
+/// Parte de `SchoolPoints` que usa este contrato.
+#[contractclient(name = "PointsClient")]
+pub trait PointsInterface {
+    fn balance(env: Env, id: Address) -> i128;
+    fn clawback(env: Env, partner: Address, from: Address, amount: i128);
+    fn mint(env: Env, minter: Address, to: Address, amount: i128, category: Symbol);
 }
 
+/// Segundos que una oferta de badge sigue aceptable (~30 días).
+const OFFER_WINDOW: u64 = 30 * 24 * 60 * 60;
+
+/// Registro de badges: `badge_id → StoredBadge`, con índice paginado por estudiante.
+/// Las ofertas pendientes no entran al índice hasta que el estudiante las acepta.
+const BADGES: Registry = Registry::new(symbol_short!("badge"));
+
+/// Registro de premios de equipo: `team_id → TeamAward`, sin índice por dueño.
+const TEAMS: Registry = Registry::new(symbol_short!("team"));
+
+/// Máximo de miembros de un equipo.
+const MAX_TEAM_SIZE: u32 = 20;
+
+/// Índice paginado class_id → badge IDs emitidos desde esa clase.
+const CLASS_BADGES: Index = Index::new(symbol_short!("cls_badge"));
+
+/// Índice paginado revisor → badge IDs que emitió.
+const REVIEWER_BADGES: Index = Index::new(symbol_short!("rev_badge"));
+
+/// Índice paginado activity_id → badge IDs entregados para esa actividad.
+const ACTIVITY_BADGES: Index = Index::new(symbol_short!("act_badge"));
+
+/// Índice paginado día de emisión (`issued_at / DAY`) → badge IDs entregados.
+const DAY_BADGES: Index = Index::new(symbol_short!("day_badge"));
+
+/// Segundos de un día: el tamaño de los buckets de `DAY_BADGES`.
+const DAY: u64 = 24 * 60 * 60;
+
+/// Índice estudiante → badge IDs que se le ofrecieron, incluidas las ofertas ya
+/// resueltas. Permite encontrar sus ofertas pendientes al migrarlo.
+const STUDENT_OFFERS: Index = Index::new(symbol_short!("offer"));
+
+/// Registro de endosos: `endorsement_id → Endorsement`, con índice paginado por
+/// endosante.
+const ENDORSEMENTS: Registry = Registry::new(symbol_short!("endorse"));
+
+/// Índice paginado objeto endosado → endorsement IDs.
+const TARGET_ENDORSEMENTS: Index = Index::new(symbol_short!("endorsed"));
+
+/// Máximo de badges destacados en la vitrina de un estudiante.
+const MAX_FEATURED: u32 = 12;
+
+/// activity_id reservado para badges que no corresponden a una actividad, como los
+/// meta-badges de las rutas. No se indexa ni se exige unicidad.
+const NO_ACTIVITY: u64 = 0;
+
 // ── Contract ──────────────────────────────────────────────────────────────────
 
 #[contract]
 pub struct AchievementBadges;
 
 #[contractimpl]
+#[allow(deprecated)]
 impl AchievementBadges {
-    /// Inicializa el contrato con el admin. Solo puede llamarse una vez.
-    pub fn initialize(env: Env, admin: Address) {
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already_initialized");
+    /// Constructor: fija el admin, el nombre y símbolo de la colección, el modo de
+    /// almacenamiento y, si se indica, el contrato `SchoolPoints` vinculado, de forma
+    /// atómica con el deploy.
+    pub fn __constructor(
+        env: Env,
+        admin: Address,
+        name: String,
+        symbol: String,
+        points_contract: Option<Address>,
+        storage_mode: StorageMode,
+    ) {
+        xpuni_common::set_admin(&env, &admin);
+        xpuni_common::set_storage_mode(&env, storage_mode);
+        xpuni_access::init(&env, &admin);
+        env.storage().instance().set(&DataKey::Name, &name);
+        env.storage().instance().set(&DataKey::Symbol, &symbol);
+        if let Some(points_contract) = points_contract {
+            env.storage()
+                .instance()
+                .set(&DataKey::PointsContract, &points_contract);
         }
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        env.storage().instance().set(&DataKey::BadgeCount, &0_u64);
     }
 
-    /// Emite un badge de logro a un estudiante. Solo el admin puede llamar esto.
-    /// Retorna el badge_id asignado.
+    /// Emite un badge de logro a un estudiante. `reviewer` firma la llamada, debe tener
+    /// el rol `Reviewer` y, si tiene un alcance, incluir `activity_id`; queda registrado
+    /// en el badge. Falla si el estudiante ya tiene un badge vigente de la actividad y
+    /// esta no es repetible. En modo `Compact`, título e imagen no se guardan: se publican
+    /// en el evento `content` y `get_badge` los retorna vacíos. Retorna el badge_id
+    /// asignado.
     pub fn issue_badge(
         env: Env,
+        reviewer: Address,
         student: Address,
         activity_id: u64,
         activity_title: String,
@@ -57,91 +425,1791 @@ impl AchievementBadges {
         points_awarded: i128,
         description_hash: BytesN<32>,
     ) -> u64 {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-
-        let badge_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::BadgeCount)
-            .unwrap_or(0);
-
-        let badge = BadgeData {
-            badge_id,
-            student: student.clone(),
-            activity_id,
+        xpuni_access::require_not_paused(&env);
+        Self::require_reviewer(&env, &reviewer, activity_id);
+        let content = BadgeContent {
             activity_title,
             image_uri,
-            reviewer: admin,
+        };
+        let badge_id = Self::issue_single(
+            &env,
+            &reviewer,
+            student,
+            activity_id,
+            content.clone(),
             points_awarded,
-            issued_at: env.ledger().timestamp(),
             description_hash,
+        );
+        if xpuni_common::storage_mode(&env) == StorageMode::Compact {
+            env.events()
+                .publish((symbol_short!("content"), badge_id), content);
+        }
+        badge_id
+    }
+
+    /// Emite un badge de `activity_id` a cada miembro de un equipo, vinculados por un
+    /// team_id común (ver `get_team`). `total_points` se reparte en proporción a
+    /// `shares`: cada miembro recibe la parte entera de su proporción y los puntos que
+    /// sobran se asignan de a uno a los miembros con mayor fracción descartada (a
+    /// igualdad, en el orden de `team_members`). Si hay un `SchoolPoints` vinculado,
+    /// acuña a cada miembro su parte en `category`; para eso este contrato debe tener
+    /// el rol `Minter` allí. El revisor se valida como en `issue_badge`. En modo `Compact`
+    /// título e imagen se publican una sola vez, en el evento `team_cnt`. Retorna el
+    /// team_id.
+    pub fn issue_team_badge(
+        env: Env,
+        reviewer: Address,
+        team_members: Vec<Address>,
+        shares: Vec<u32>,
+        total_points: i128,
+        activity_id: u64,
+        activity_title: String,
+        image_uri: String,
+        description_hash: BytesN<32>,
+        category: Symbol,
+    ) -> u64 {
+        xpuni_access::require_not_paused(&env);
+        Self::require_reviewer(&env, &reviewer, activity_id);
+        if team_members.is_empty() {
+            panic!("empty_team");
+        }
+        if team_members.len() > MAX_TEAM_SIZE {
+            panic!("team_too_large");
+        }
+        if shares.len() != team_members.len() {
+            panic!("shares_length_mismatch");
+        }
+        if total_points < 0 {
+            panic!("negative_points");
+        }
+        for (i, member) in team_members.iter().enumerate() {
+            if team_members.first_index_of(&member) != Some(i as u32) {
+                panic!("duplicate_member");
+            }
+        }
+
+        let points = Self::split_points(&env, total_points, &shares);
+        let points_contract = Self::points_contract(env.clone());
+        let content = BadgeContent {
+            activity_title,
+            image_uri,
         };
+        let mut badge_ids = Vec::new(&env);
+        for (member, amount) in team_members.iter().zip(points.iter()) {
+            let badge_id = Self::issue_single(
+                &env,
+                &reviewer,
+                member.clone(),
+                activity_id,
+                content.clone(),
+                amount,
+                description_hash.clone(),
+            );
+            badge_ids.push_back(badge_id);
+            if let (Some(points_contract), true) = (&points_contract, amount > 0) {
+                PointsClient::new(&env, points_contract).mint(
+                    &env.current_contract_address(),
+                    &member,
+                    &amount,
+                    &category,
+                );
+            }
+        }
 
-        // Persistir badge con TTL extendido (~10M ledgers ≈ varios años)
-        env.storage()
-            .persistent()
-            .set(&DataKey::Badge(badge_id), &badge);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Badge(badge_id), 10_000_000, 10_000_000);
+        let team_id = TEAMS.insert(&env, |team_id| TeamAward {
+            team_id,
+            activity_id,
+            members: team_members,
+            shares,
+            total_points,
+            badge_ids: badge_ids.clone(),
+            issued_at: env.ledger().timestamp(),
+        });
+        for badge_id in badge_ids.iter() {
+            let key = DataKey::TeamOf(badge_id);
+            env.storage().persistent().set(&key, &team_id);
+            xpuni_common::extend_persistent(&env, &key);
+        }
 
-        // Actualizar lista de badges del estudiante
-        let mut student_badges: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::StudentBadges(student.clone()))
-            .unwrap_or_else(|| Vec::new(&env));
+        env.events()
+            .publish((symbol_short!("team"), team_id), badge_ids);
+        if xpuni_common::storage_mode(&env) == StorageMode::Compact {
+            env.events()
+                .publish((symbol_short!("team_cnt"), team_id), content);
+        }
 
-        student_badges.push_back(badge_id);
+        team_id
+    }
 
-        env.storage()
-            .persistent()
-            .set(&DataKey::StudentBadges(student.clone()), &student_badges);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::StudentBadges(student.clone()), 10_000_000, 10_000_000);
+    /// Retorna un premio de equipo por su ID.
+    pub fn get_team(env: Env, team_id: u64) -> TeamAward {
+        TEAMS
+            .get(&env, team_id)
+            .unwrap_or_else(|| panic!("team_not_found"))
+    }
 
-        // Incrementar contador
-        env.storage()
-            .instance()
-            .set(&DataKey::BadgeCount, &(badge_id + 1));
+    /// team_id del premio de equipo al que pertenece el badge, si lo hay.
+    pub fn team_of(env: Env, badge_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::TeamOf(badge_id))
+    }
+
+    /// Emite un badge de la clase `class_id`, que debe estar activa. Título, imagen y
+    /// puntos se toman de la clase. El revisor se valida como en `issue_badge`. Si la
+    /// clase requiere aceptación, el badge queda como oferta hasta que el estudiante
+    /// llame `accept_badge`. Retorna el badge_id.
+    pub fn issue_class_badge(
+        env: Env,
+        reviewer: Address,
+        student: Address,
+        class_id: u64,
+        activity_id: u64,
+        description_hash: BytesN<32>,
+    ) -> u64 {
+        xpuni_access::require_not_paused(&env);
+        Self::require_reviewer(&env, &reviewer, activity_id);
+        Self::issue_from_class(
+            &env,
+            reviewer,
+            student,
+            class_id,
+            activity_id,
+            description_hash,
+            true,
+        )
+    }
+
+    /// El estudiante acepta una oferta pendiente antes de que venza. Desde ese momento
+    /// el badge aparece en `get_student_badges`. Una oferta revocada ya no puede
+    /// aceptarse; solo rechazarse.
+    pub fn accept_badge(env: Env, badge_id: u64) {
+        xpuni_access::require_not_paused(&env);
+        let expires_at = Self::pending_offer(env.clone(), badge_id)
+            .unwrap_or_else(|| panic!("offer_not_found"));
+        let badge = Self::get_badge(env.clone(), badge_id);
+        badge.student.require_auth();
+        if env.ledger().timestamp() > expires_at {
+            panic!("offer_expired");
+        }
+        if env.storage().persistent().has(&DataKey::Revocation(badge_id)) {
+            panic!("badge_revoked");
+        }
+
+        env.storage().persistent().remove(&DataKey::Offer(badge_id));
+        Self::deliver(
+            &env,
+            badge_id,
+            &badge.reviewer,
+            badge.student,
+            badge.class_id,
+            badge.activity_id,
+            badge.issued_at,
+        );
+    }
 
-        // Emitir evento on-chain
+    /// El estudiante rechaza una oferta pendiente, vencida o no. El badge se borra.
+    pub fn decline_badge(env: Env, badge_id: u64) {
+        Self::pending_offer(env.clone(), badge_id).unwrap_or_else(|| panic!("offer_not_found"));
+        let badge = Self::get_badge(env.clone(), badge_id);
+        badge.student.require_auth();
+
+        Self::discard_offer(&env, badge_id, &badge.student, badge.activity_id);
         env.events()
-            .publish((symbol_short!("badge"), student, badge_id), badge_id);
+            .publish((symbol_short!("decline"), badge.student, badge_id), badge_id);
+    }
 
-        badge_id
+    /// Timestamp en que vence la oferta `badge_id`, si está pendiente.
+    pub fn pending_offer(env: Env, badge_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::Offer(badge_id))
     }
 
     /// Retorna los datos de un badge por su ID.
     pub fn get_badge(env: Env, badge_id: u64) -> BadgeData {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Badge(badge_id))
-            .unwrap_or_else(|| panic!("badge_not_found"))
+        Self::load_badge(&env, badge_id).unwrap_or_else(|| panic!("badge_not_found"))
     }
 
-    /// Retorna todos los badge IDs de un estudiante.
-    pub fn get_student_badges(env: Env, student: Address) -> Vec<u64> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::StudentBadges(student))
-            .unwrap_or_else(|| Vec::new(&env))
+    /// Retorna hasta `limit` badge IDs de un estudiante a partir de la posición `start`,
+    /// en orden de emisión.
+    pub fn get_student_badges(env: Env, student: Address, start: u32, limit: u32) -> Vec<u64> {
+        BADGES.ids_of(&env, &student, start, limit)
+    }
+
+    /// Retorna cuántos badges tiene un estudiante.
+    pub fn student_badge_count(env: Env, student: Address) -> u32 {
+        BADGES.count_of(&env, &student)
     }
 
     /// Retorna el total de badges emitidos.
     pub fn badge_count(env: Env) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::BadgeCount)
-            .unwrap_or(0)
+        BADGES.count(&env)
     }
 
     /// Retorna la dirección del admin.
     pub fn admin(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::Admin).unwrap()
+        xpuni_common::admin(&env)
     }
-}
+
+    /// Retorna el modo de almacenamiento elegido en el constructor.
+    pub fn storage_mode(env: Env) -> StorageMode {
+        xpuni_common::storage_mode(&env)
+    }
+
+    /// Retorna el contrato `SchoolPoints` vinculado, si lo hay.
+    pub fn points_contract(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::PointsContract)
+    }
+
+    // ── Revocación ────────────────────────────────────────────────────────────
+
+    /// Revoca un badge. Puede hacerlo un `Admin` o el revisor que lo emitió.
+    ///
+    /// Con `clawback`, retira además del estudiante los `points_awarded` en el
+    /// `SchoolPoints` vinculado (o su saldo, si ya gastó parte). Para eso este contrato
+    /// debe tener el rol `Partner` en `SchoolPoints`.
+    ///
+    /// No descuenta el badge del avance en rutas (ver `LearningPath`).
+    pub fn revoke_badge(
+        env: Env,
+        caller: Address,
+        badge_id: u64,
+        reason_hash: BytesN<32>,
+        clawback: bool,
+    ) {
+        let badge = Self::get_badge(env.clone(), badge_id);
+        Self::require_admin_or_reviewer(&env, &caller, &badge);
+        let key = DataKey::Revocation(badge_id);
+        if env.storage().persistent().has(&key) {
+            panic!("badge_already_revoked");
+        }
+
+        let mut points_clawed_back = 0;
+        if clawback && badge.points_awarded > 0 {
+            let points_contract = Self::points_contract(env.clone())
+                .unwrap_or_else(|| panic!("points_contract_not_set"));
+            let points = PointsClient::new(&env, &points_contract);
+            points_clawed_back = badge.points_awarded.min(points.balance(&badge.student));
+            if points_clawed_back > 0 {
+                points.clawback(
+                    &env.current_contract_address(),
+                    &badge.student,
+                    &points_clawed_back,
+                );
+            }
+        }
+
+        let revocation = Revocation {
+            revoked_by: caller,
+            revoked_at: env.ledger().timestamp(),
+            reason_hash: reason_hash.clone(),
+            points_clawed_back,
+        };
+        env.storage().persistent().set(&key, &revocation);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events().publish(
+            (symbol_short!("revoke"), badge.student, badge_id),
+            (reason_hash, points_clawed_back),
+        );
+    }
+
+    /// Retorna la revocación de un badge, si fue revocado.
+    pub fn get_revocation(env: Env, badge_id: u64) -> Option<Revocation> {
+        env.storage().persistent().get(&DataKey::Revocation(badge_id))
+    }
+
+    /// Indica si el badge existe, fue aceptado, no ha sido revocado y no ha vencido.
+    pub fn is_valid(env: Env, badge_id: u64) -> bool {
+        BADGES.get::<StoredBadge>(&env, badge_id).is_some()
+            && !env.storage().persistent().has(&DataKey::Revocation(badge_id))
+            && !env.storage().persistent().has(&DataKey::Offer(badge_id))
+            && !Self::is_expired(&env, badge_id)
+    }
+
+    // ── Endosos ───────────────────────────────────────────────────────────────
+
+    /// `endorser` respalda públicamente un badge o una clase. Requiere su firma; cada
+    /// endosante tiene a lo sumo un endoso vigente por objeto. Retorna el
+    /// endorsement_id.
+    pub fn endorse(
+        env: Env,
+        endorser: Address,
+        target: EndorsementTarget,
+        comment_hash: BytesN<32>,
+    ) -> u64 {
+        xpuni_access::require_not_paused(&env);
+        endorser.require_auth();
+        match target {
+            EndorsementTarget::Badge(badge_id) => {
+                Self::get_badge(env.clone(), badge_id);
+            }
+            EndorsementTarget::Class(class_id) => {
+                Self::get_badge_class(env.clone(), class_id);
+            }
+        }
+        let endorsed_key = DataKey::Endorsed(target.clone(), endorser.clone());
+        if env.storage().persistent().has(&endorsed_key) {
+            panic!("already_endorsed");
+        }
+
+        let endorsement_id = ENDORSEMENTS.append(&env, &endorser, |endorsement_id| Endorsement {
+            endorsement_id,
+            target: target.clone(),
+            endorser: endorser.clone(),
+            comment_hash,
+            endorsed_at: env.ledger().timestamp(),
+            revoked: false,
+        });
+        TARGET_ENDORSEMENTS.push(&env, &target, endorsement_id);
+        env.storage().persistent().set(&endorsed_key, &endorsement_id);
+        xpuni_common::extend_persistent(&env, &endorsed_key);
+
+        env.events()
+            .publish((symbol_short!("endorse"), endorser, endorsement_id), target);
+
+        endorsement_id
+    }
+
+    /// El endosante retira su propio endoso. Sigue listado, marcado como revocado.
+    pub fn revoke_endorsement(env: Env, endorser: Address, endorsement_id: u64) {
+        endorser.require_auth();
+        let mut endorsement = Self::get_endorsement(env.clone(), endorsement_id);
+        if endorsement.endorser != endorser {
+            panic!("not_endorser");
+        }
+        if endorsement.revoked {
+            panic!("endorsement_already_revoked");
+        }
+
+        endorsement.revoked = true;
+        ENDORSEMENTS.replace(&env, endorsement_id, &endorsement);
+        env.storage().persistent().remove(&DataKey::Endorsed(
+            endorsement.target.clone(),
+            endorser.clone(),
+        ));
+
+        env.events().publish(
+            (symbol_short!("unendorse"), endorser, endorsement_id),
+            endorsement.target,
+        );
+    }
+
+    /// Retorna un endoso por su ID.
+    pub fn get_endorsement(env: Env, endorsement_id: u64) -> Endorsement {
+        ENDORSEMENTS
+            .get(&env, endorsement_id)
+            .unwrap_or_else(|| panic!("endorsement_not_found"))
+    }
+
+    /// Retorna hasta `limit` endosos de `target` a partir de la posición `start`, en
+    /// orden de creación, incluidos los revocados.
+    pub fn endorsements_of(
+        env: Env,
+        target: EndorsementTarget,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Endorsement> {
+        let mut result = Vec::new(&env);
+        for endorsement_id in TARGET_ENDORSEMENTS.page(&env, &target, start, limit).iter() {
+            result.push_back(Self::get_endorsement(env.clone(), endorsement_id));
+        }
+        result
+    }
+
+    /// Retorna cuántos endosos recibió `target`, incluidos los revocados.
+    pub fn endorsement_count(env: Env, target: EndorsementTarget) -> u32 {
+        TARGET_ENDORSEMENTS.len(&env, &target)
+    }
+
+    /// Retorna hasta `limit` endorsement IDs creados por `endorser` a partir de la
+    /// posición `start`.
+    pub fn endorsements_by(env: Env, endorser: Address, start: u32, limit: u32) -> Vec<u64> {
+        ENDORSEMENTS.ids_of(&env, &endorser, start, limit)
+    }
+
+    // ── Vitrina ───────────────────────────────────────────────────────────────
+    //
+    // Cada estudiante decide qué badges muestra su perfil público: destaca algunos en
+    // orden y oculta los que no quiere mostrar. Solo afecta a `showcase`;
+    // `get_student_badges` sigue listando todos.
+
+    /// Reemplaza los badges destacados del estudiante (hasta `MAX_FEATURED`, en el
+    /// orden dado). Requiere su firma. Destacar un badge oculto lo vuelve visible; uno
+    /// revocado no puede destacarse.
+    pub fn set_featured(env: Env, student: Address, badge_ids: Vec<u64>) {
+        student.require_auth();
+        if badge_ids.len() > MAX_FEATURED {
+            panic!("too_many_featured");
+        }
+        for (i, badge_id) in badge_ids.iter().enumerate() {
+            Self::require_owned(&env, &student, badge_id);
+            if env.storage().persistent().has(&DataKey::Revocation(badge_id)) {
+                panic!("badge_revoked");
+            }
+            if badge_ids.first_index_of(badge_id) != Some(i as u32) {
+                panic!("duplicate_featured");
+            }
+            env.storage().persistent().remove(&DataKey::Hidden(badge_id));
+        }
+
+        let key = DataKey::Featured(student.clone());
+        env.storage().persistent().set(&key, &badge_ids);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("showcase"), student), badge_ids);
+    }
+
+    /// Oculta o vuelve a mostrar un badge en la vitrina de su dueño. Requiere su firma.
+    /// Ocultar un badge lo quita de los destacados.
+    pub fn set_badge_hidden(env: Env, student: Address, badge_id: u64, hidden: bool) {
+        student.require_auth();
+        Self::require_owned(&env, &student, badge_id);
+
+        let key = DataKey::Hidden(badge_id);
+        if hidden {
+            env.storage().persistent().set(&key, &true);
+            xpuni_common::extend_persistent(&env, &key);
+
+            let featured_key = DataKey::Featured(student.clone());
+            let mut featured = Self::featured_badges(env.clone(), student.clone());
+            if let Some(i) = featured.first_index_of(badge_id) {
+                featured.remove(i);
+                env.storage().persistent().set(&featured_key, &featured);
+            }
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        env.events()
+            .publish((symbol_short!("hidden"), student, badge_id), hidden);
+    }
+
+    /// Badges destacados del estudiante, en su orden.
+    pub fn featured_badges(env: Env, student: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Featured(student))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Indica si el dueño ocultó el badge de su vitrina.
+    pub fn is_hidden(env: Env, badge_id: u64) -> bool {
+        env.storage().persistent().has(&DataKey::Hidden(badge_id))
+    }
+
+    /// Vitrina pública del estudiante: primero sus destacados, en orden, y después el
+    /// resto de sus badges no ocultos en orden de emisión, hasta `MAX_PAGE_SIZE`. Los
+    /// badges revocados no aparecen, aunque se hayan destacado antes de la revocación.
+    pub fn showcase(env: Env, student: Address) -> Vec<u64> {
+        let featured = Self::featured_badges(env.clone(), student.clone());
+        let mut result = Vec::new(&env);
+        for badge_id in featured.iter() {
+            if !env.storage().persistent().has(&DataKey::Revocation(badge_id)) {
+                result.push_back(badge_id);
+            }
+        }
+        let mut n = 0;
+        while result.len() < MAX_PAGE_SIZE && n < BADGES.count_of(&env, &student) {
+            let page = BADGES.ids_of(&env, &student, n, MAX_PAGE_SIZE);
+            for badge_id in page.iter() {
+                if result.len() < MAX_PAGE_SIZE
+                    && !featured.contains(badge_id)
+                    && !Self::is_hidden(env.clone(), badge_id)
+                    && !env.storage().persistent().has(&DataKey::Revocation(badge_id))
+                {
+                    result.push_back(badge_id);
+                }
+            }
+            n += page.len();
+        }
+        result
+    }
+
+    // ── Vencimiento ───────────────────────────────────────────────────────────
+
+    /// Da a los badges que emita `class_id` una validez de `validity_secs` desde su
+    /// emisión (p. ej. un año para primeros auxilios). Con `None`, no vencen. Requiere
+    /// el rol `Admin`.
+    pub fn set_class_validity(
+        env: Env,
+        caller: Address,
+        class_id: u64,
+        validity_secs: Option<u64>,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        Self::get_badge_class(env.clone(), class_id);
+        let key = DataKey::ClassValidity(class_id);
+        match validity_secs {
+            Some(secs) => {
+                env.storage().persistent().set(&key, &secs);
+                xpuni_common::extend_persistent(&env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Timestamp hasta el que el badge es válido, si vence.
+    pub fn valid_until(env: Env, badge_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::ValidUntil(badge_id))
+    }
+
+    /// Extiende (o fija por primera vez) la validez de un badge no revocado hasta
+    /// `new_valid_until`, que debe ser futuro. Puede hacerlo un `Admin` o el revisor
+    /// que lo emitió. La renovación queda en `renewal_history`.
+    pub fn renew_badge(env: Env, caller: Address, badge_id: u64, new_valid_until: u64) {
+        let badge = Self::get_badge(env.clone(), badge_id);
+        Self::require_admin_or_reviewer(&env, &caller, &badge);
+        if env.storage().persistent().has(&DataKey::Revocation(badge_id)) {
+            panic!("badge_revoked");
+        }
+        let now = env.ledger().timestamp();
+        if new_valid_until <= now {
+            panic!("valid_until_in_past");
+        }
+
+        let previous_valid_until = Self::valid_until(env.clone(), badge_id);
+        Self::set_valid_until(&env, badge_id, new_valid_until);
+
+        let history_key = DataKey::Renewals(badge_id);
+        let mut history = Self::renewal_history(env.clone(), badge_id);
+        history.push_back(Renewal {
+            renewed_by: caller,
+            renewed_at: now,
+            previous_valid_until,
+            valid_until: new_valid_until,
+        });
+        env.storage().persistent().set(&history_key, &history);
+        xpuni_common::extend_persistent(&env, &history_key);
+
+        env.events().publish(
+            (symbol_short!("renewed"), badge.student, badge_id),
+            new_valid_until,
+        );
+    }
+
+    /// Renovaciones de un badge, de la más antigua a la más reciente.
+    pub fn renewal_history(env: Env, badge_id: u64) -> Vec<Renewal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Renewals(badge_id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Publica el evento `expired` de un badge vencido, una vez por vencimiento, para
+    /// que el frontend muestre el recordatorio de renovación. Cualquiera puede llamarla.
+    pub fn mark_expired(env: Env, badge_id: u64) {
+        let badge = Self::get_badge(env.clone(), badge_id);
+        if !Self::is_expired(&env, badge_id) {
+            panic!("badge_not_expired");
+        }
+        let valid_until = Self::valid_until(env.clone(), badge_id).unwrap();
+        let key = DataKey::ExpiredAt(badge_id);
+        if env.storage().persistent().get(&key) == Some(valid_until) {
+            panic!("expiry_already_marked");
+        }
+        env.storage().persistent().set(&key, &valid_until);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("expired"), badge.student, badge_id), valid_until);
+    }
+
+    // ── Revisores ─────────────────────────────────────────────────────────────
+    //
+    // Los revisores son las cuentas con el rol `Reviewer` (ver `grant_role`). Además,
+    // el admin puede limitar a cada uno a ciertos activity IDs.
+
+    /// Limita a `reviewer` a emitir badges solo de `activity_ids`. Con la lista vacía
+    /// se quita el límite. Requiere el rol `Admin`.
+    pub fn set_reviewer_scope(
+        env: Env,
+        caller: Address,
+        reviewer: Address,
+        activity_ids: Vec<u64>,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        let key = DataKey::ReviewerScope(reviewer.clone());
+        if activity_ids.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &activity_ids);
+            xpuni_common::extend_persistent(&env, &key);
+        }
+
+        env.events()
+            .publish((symbol_short!("rev_scope"), reviewer), activity_ids);
+    }
+
+    /// Activity IDs a los que está limitado `reviewer`. Vacío si no tiene límite.
+    pub fn reviewer_scope(env: Env, reviewer: Address) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReviewerScope(reviewer))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Retorna hasta `limit` badge IDs emitidos por `reviewer` a partir de la posición
+    /// `start`, en orden de emisión.
+    pub fn badges_by_reviewer(env: Env, reviewer: Address, start: u32, limit: u32) -> Vec<u64> {
+        REVIEWER_BADGES.page(&env, &reviewer, start, limit)
+    }
+
+    /// Retorna cuántos badges emitió `reviewer`.
+    pub fn reviewer_badge_count(env: Env, reviewer: Address) -> u32 {
+        REVIEWER_BADGES.len(&env, &reviewer)
+    }
+
+    // ── Actividades ───────────────────────────────────────────────────────────
+
+    /// Último badge emitido a `student` para `activity_id`, incluidas ofertas
+    /// pendientes y badges revocados.
+    pub fn badge_for(env: Env, student: Address, activity_id: u64) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BadgeFor(student, activity_id))
+    }
+
+    /// Permite (o vuelve a impedir) varios badges por estudiante para `activity_id`.
+    /// Requiere el rol `Admin`.
+    pub fn set_activity_repeatable(
+        env: Env,
+        caller: Address,
+        activity_id: u64,
+        repeatable: bool,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        let key = DataKey::Repeatable(activity_id);
+        if repeatable {
+            env.storage().persistent().set(&key, &true);
+            xpuni_common::extend_persistent(&env, &key);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
+
+    /// Indica si `activity_id` admite varios badges por estudiante.
+    pub fn activity_repeatable(env: Env, activity_id: u64) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Repeatable(activity_id))
+            .unwrap_or(false)
+    }
+
+    /// Retorna hasta `limit` badge IDs entregados para `activity_id` a partir de la
+    /// posición `start`, en orden de emisión.
+    pub fn badges_of_activity(env: Env, activity_id: u64, start: u32, limit: u32) -> Vec<u64> {
+        ACTIVITY_BADGES.page(&env, &activity_id, start, limit)
+    }
+
+    /// Retorna cuántos badges se entregaron para `activity_id`.
+    pub fn activity_badge_count(env: Env, activity_id: u64) -> u32 {
+        ACTIVITY_BADGES.len(&env, &activity_id)
+    }
+
+    // ── Consultas ─────────────────────────────────────────────────────────────
+
+    /// Retorna hasta `limit` badge IDs entregados con fecha de emisión en el día `day`
+    /// (`timestamp / 86_400`, UTC) a partir de la posición `start`.
+    pub fn badges_on_day(env: Env, day: u64, start: u32, limit: u32) -> Vec<u64> {
+        DAY_BADGES.page(&env, &day, start, limit)
+    }
+
+    /// Retorna cuántos badges entregados se emitieron en el día `day`.
+    pub fn day_badge_count(env: Env, day: u64) -> u32 {
+        DAY_BADGES.len(&env, &day)
+    }
+
+    /// Busca badges entregados que cumplan `query`. Recorre el índice más selectivo
+    /// disponible (actividad, revisor o días del rango) y examina a lo sumo `limit`
+    /// entradas (máximo `MAX_PAGE_SIZE`) por llamada, contando cada día vacío como una;
+    /// por eso una página puede traer menos resultados aunque queden más. Retorna los
+    /// badge IDs y el cursor desde el que seguir, `None` cuando no quedan más.
+    pub fn query_badges(
+        env: Env,
+        query: BadgeQuery,
+        cursor: Option<QueryCursor>,
+        limit: u32,
+    ) -> (Vec<u64>, Option<QueryCursor>) {
+        let cursor = cursor.unwrap_or(QueryCursor {
+            day: query.from / DAY,
+            position: 0,
+        });
+        let budget = limit.min(MAX_PAGE_SIZE);
+        let (page, len) = match (&query.activity_id, &query.reviewer) {
+            (Some(activity_id), _) => (
+                ACTIVITY_BADGES.page(&env, activity_id, cursor.position, budget),
+                ACTIVITY_BADGES.len(&env, activity_id),
+            ),
+            (None, Some(reviewer)) => (
+                REVIEWER_BADGES.page(&env, reviewer, cursor.position, budget),
+                REVIEWER_BADGES.len(&env, reviewer),
+            ),
+            (None, None) => return Self::query_by_day(&env, &query, cursor, budget),
+        };
+
+        let mut badge_ids = Vec::new(&env);
+        for badge_id in page.iter() {
+            if Self::matches_query(&env, &query, badge_id) {
+                badge_ids.push_back(badge_id);
+            }
+        }
+        let position = cursor.position + page.len();
+        let next = (position < len).then_some(QueryCursor { position, ..cursor });
+        (badge_ids, next)
+    }
+
+    // ── Rutas de aprendizaje ──────────────────────────────────────────────────
+
+    /// Define una ruta de aprendizaje cuyo premio es un badge de `meta_class_id`. Con
+    /// `threshold` en 0 exige todos los requisitos; si no, `threshold` badges que
+    /// cumplan alguno. La clase del meta-badge no puede tener prerequisito: se emite al
+    /// completar la ruta, sin otra comprobación. Requiere el rol `Admin`.
+    pub fn create_path(
+        env: Env,
+        caller: Address,
+        path_id: u64,
+        meta_class_id: u64,
+        requirements: Vec<Requirement>,
+        threshold: u32,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        Self::get_badge_class(env.clone(), meta_class_id);
+        if requirements.is_empty() {
+            panic!("empty_path");
+        }
+        if Self::class_prerequisite(env.clone(), meta_class_id).is_some() {
+            panic!("meta_class_has_prerequisite");
+        }
+        let key = DataKey::Path(path_id);
+        if env.storage().persistent().has(&key) {
+            panic!("path_already_exists");
+        }
+
+        let path = LearningPath {
+            path_id,
+            meta_class_id,
+            requirements: requirements.clone(),
+            threshold,
+        };
+        env.storage().persistent().set(&key, &path);
+        xpuni_common::extend_persistent(&env, &key);
+        let meta_key = DataKey::MetaClass(meta_class_id);
+        env.storage().persistent().set(&meta_key, &true);
+        xpuni_common::extend_persistent(&env, &meta_key);
+
+        for requirement in requirements.iter() {
+            let for_key = DataKey::PathsFor(requirement);
+            let mut path_ids: Vec<u64> = env
+                .storage()
+                .persistent()
+                .get(&for_key)
+                .unwrap_or_else(|| Vec::new(&env));
+            path_ids.push_back(path_id);
+            env.storage().persistent().set(&for_key, &path_ids);
+            xpuni_common::extend_persistent(&env, &for_key);
+        }
+
+        env.events()
+            .publish((symbol_short!("path"), path_id), meta_class_id);
+    }
+
+    /// Retorna una ruta por su ID.
+    pub fn get_path(env: Env, path_id: u64) -> LearningPath {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Path(path_id))
+            .unwrap_or_else(|| panic!("path_not_found"))
+    }
+
+    /// Avance de `student` en la ruta `path_id`.
+    pub fn path_progress(env: Env, student: Address, path_id: u64) -> PathProgress {
+        let path = Self::get_path(env.clone(), path_id);
+        let needed = if path.threshold > 0 {
+            path.threshold
+        } else {
+            path.requirements.len()
+        };
+        env.storage()
+            .persistent()
+            .get(&DataKey::Progress(student, path_id))
+            .unwrap_or(PathProgress {
+                path_id,
+                matched_badges: 0,
+                satisfied: Vec::new(&env),
+                needed,
+                completed: false,
+                meta_badge_id: None,
+            })
+    }
+
+    /// Exige completar la ruta `path_id` antes de recibir badges de `class_id`. Con
+    /// `None` se quita el requisito. No aplica a clases que son meta-badge de una ruta.
+    /// Requiere el rol `Admin`.
+    pub fn set_class_prerequisite(
+        env: Env,
+        caller: Address,
+        class_id: u64,
+        path_id: Option<u64>,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        Self::get_badge_class(env.clone(), class_id);
+        let key = DataKey::ClassPrereq(class_id);
+        match path_id {
+            Some(path_id) => {
+                Self::get_path(env.clone(), path_id);
+                if env.storage().persistent().has(&DataKey::MetaClass(class_id)) {
+                    panic!("meta_class_has_prerequisite");
+                }
+                env.storage().persistent().set(&key, &path_id);
+                xpuni_common::extend_persistent(&env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+    }
+
+    /// Ruta que hay que completar antes de recibir badges de `class_id`, si la hay.
+    pub fn class_prerequisite(env: Env, class_id: u64) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::ClassPrereq(class_id))
+    }
+
+    // ── Lotes Merkle ──────────────────────────────────────────────────────────
+
+    /// Publica un lote de badges de `class_id` como una raíz de Merkle sobre sus
+    /// `BatchLeaf`. Requiere el rol `Admin`.
+    pub fn publish_batch(
+        env: Env,
+        caller: Address,
+        batch_id: u64,
+        merkle_root: BytesN<32>,
+        class_id: u64,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        Self::get_badge_class(env.clone(), class_id);
+        let key = DataKey::Batch(batch_id);
+        if env.storage().persistent().has(&key) {
+            panic!("batch_already_exists");
+        }
+
+        let batch = Batch {
+            batch_id,
+            merkle_root: merkle_root.clone(),
+            class_id,
+            published_by: caller,
+            published_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(&key, &batch);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("batch"), batch_id), (merkle_root, class_id));
+    }
+
+    /// Retorna un lote por su ID.
+    pub fn get_batch(env: Env, batch_id: u64) -> Batch {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Batch(batch_id))
+            .unwrap_or_else(|| panic!("batch_not_found"))
+    }
+
+    /// El estudiante de `leaf` reclama su badge del lote. `proof` son los hermanos de
+    /// la hoja desde abajo hacia la raíz. Cada hoja se reclama una sola vez. Retorna el
+    /// badge_id emitido.
+    pub fn claim(env: Env, batch_id: u64, leaf: BatchLeaf, proof: Vec<BytesN<32>>) -> u64 {
+        xpuni_access::require_not_paused(&env);
+        leaf.student.require_auth();
+        let batch = Self::get_batch(env.clone(), batch_id);
+
+        let leaf_hash = Self::leaf_hash(&env, &leaf);
+        let claimed_key = DataKey::Claimed(batch_id, leaf_hash.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            panic!("leaf_already_claimed");
+        }
+        let mut node = leaf_hash;
+        for sibling in proof.iter() {
+            node = Self::hash_pair(&env, &node, &sibling);
+        }
+        if node != batch.merkle_root {
+            panic!("invalid_proof");
+        }
+
+        let badge_id = Self::issue_from_class(
+            &env,
+            batch.published_by,
+            leaf.student.clone(),
+            batch.class_id,
+            leaf.activity_id,
+            leaf.description_hash,
+            false,
+        );
+        env.storage().persistent().set(&claimed_key, &badge_id);
+        xpuni_common::extend_persistent(&env, &claimed_key);
+
+        env.events()
+            .publish((symbol_short!("claim"), batch_id, leaf.student), badge_id);
+
+        badge_id
+    }
+
+    /// Retorna el badge emitido al reclamar `leaf` en el lote, si ya se reclamó.
+    pub fn claimed_badge(env: Env, batch_id: u64, leaf: BatchLeaf) -> Option<u64> {
+        let leaf_hash = Self::leaf_hash(&env, &leaf);
+        env.storage()
+            .persistent()
+            .get(&DataKey::Claimed(batch_id, leaf_hash))
+    }
+
+    // ── Verificación ──────────────────────────────────────────────────────────
+
+    /// Indica si `preimage` es la evidencia anclada en el badge: su SHA-256 coincide
+    /// con `description_hash`. Retorna `false` si el badge no existe.
+    pub fn verify_evidence(env: Env, badge_id: u64, preimage: Bytes) -> bool {
+        match Self::load_badge(&env, badge_id) {
+            Some(badge) => env.crypto().sha256(&preimage).to_bytes() == badge.description_hash,
+            None => false,
+        }
+    }
+
+    /// Estado de un badge respecto de `student`: si existe, quién es su dueño y si
+    /// está revocado o vencido.
+    pub fn verify_badge(env: Env, badge_id: u64, student: Address) -> BadgeStatus {
+        let owner = Self::load_badge(&env, badge_id).map(|badge| badge.student);
+        BadgeStatus {
+            exists: owner.is_some(),
+            owned_by_student: owner.as_ref() == Some(&student),
+            owner,
+            revoked: env.storage().persistent().has(&DataKey::Revocation(badge_id)),
+            pending: env.storage().persistent().has(&DataKey::Offer(badge_id)),
+            expired: Self::is_expired(&env, badge_id),
+        }
+    }
+
+    /// Indica si `content` son el título y la imagen del badge. En modo `Compact`
+    /// recalcula su hash y lo compara con el guardado; si el badge fue borrado (ver
+    /// `redact`), compara el título con `redacted_hash`; si no, compara los campos.
+    pub fn verify_content(env: Env, badge_id: u64, content: BadgeContent) -> bool {
+        match BADGES.get(&env, badge_id) {
+            Some(StoredBadge::Compact(badge)) => {
+                xpuni_common::content_hash(&env, &content) == badge.content_hash
+            }
+            Some(StoredBadge::Full(badge)) => match Self::redacted_hash(env.clone(), badge_id) {
+                Some(hash) => {
+                    env.crypto().sha256(&content.activity_title.to_bytes()).to_bytes() == hash
+                        && badge.image_uri == content.image_uri
+                }
+                None => {
+                    badge.activity_title == content.activity_title
+                        && badge.image_uri == content.image_uri
+                }
+            },
+            Some(StoredBadge::Class(_)) => {
+                let badge = Self::get_badge(env, badge_id);
+                badge.activity_title == content.activity_title
+                    && badge.image_uri == content.image_uri
+            }
+            None => false,
+        }
+    }
+
+    // ── Recuperación de wallet ────────────────────────────────────────────────
+
+    /// Pasa todos los badges del estudiante `old`, que perdió su wallet, a `new`, junto
+    /// con sus ofertas pendientes, su avance en rutas y su vitrina, y deja registrado el
+    /// enlace `old → new`. Requiere el rol `Admin`. Retorna la cantidad de badges
+    /// entregados migrados.
+    pub fn migrate_student(env: Env, caller: Address, old: Address, new: Address) -> u32 {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        xpuni_common::record_migration(&env, &old, &new);
+
+        let moved = BADGES.move_owner(&env, &old, &new);
+        let mut path_ids: Vec<u64> = Vec::new(&env);
+        for badge_id in moved.iter() {
+            for requirement in Self::reassign_badge(&env, badge_id, &old, &new).iter() {
+                let ids: Vec<u64> = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::PathsFor(requirement))
+                    .unwrap_or_else(|| Vec::new(&env));
+                for path_id in ids.iter() {
+                    if !path_ids.contains(path_id) {
+                        path_ids.push_back(path_id);
+                    }
+                }
+            }
+        }
+
+        // Solo los badges entregados hacen avanzar rutas, así que sus requisitos cubren
+        // todas las rutas con avance del estudiante. Si `new` ya avanzó en alguna, los
+        // dos avances se suman y la ruta puede quedar completa con la migración.
+        for path_id in path_ids.iter() {
+            let old_key = DataKey::Progress(old.clone(), path_id);
+            let moved: Option<PathProgress> = env.storage().persistent().get(&old_key);
+            let Some(moved) = moved else {
+                continue;
+            };
+            env.storage().persistent().remove(&old_key);
+
+            let path = Self::get_path(env.clone(), path_id);
+            let meta_class = Self::get_badge_class(env.clone(), path.meta_class_id);
+            let mut progress = Self::path_progress(env.clone(), new.clone(), path_id);
+            progress.matched_badges += moved.matched_badges;
+            for requirement in moved.satisfied.iter() {
+                if !progress.satisfied.contains(&requirement) {
+                    progress.satisfied.push_back(requirement);
+                }
+            }
+            progress.meta_badge_id = progress.meta_badge_id.or(moved.meta_badge_id);
+            progress.completed = progress.completed
+                || moved.completed
+                || (meta_class.active && Self::path_complete(&path, &progress));
+            Self::store_progress(&env, &new, &path, &meta_class, progress);
+        }
+
+        for badge_id in STUDENT_OFFERS.move_all(&env, &old, &new).iter() {
+            if env.storage().persistent().has(&DataKey::Offer(badge_id)) {
+                Self::reassign_badge(&env, badge_id, &old, &new);
+            }
+        }
+
+        let featured_key = DataKey::Featured(old.clone());
+        if let Some(featured) = env.storage().persistent().get::<_, Vec<u64>>(&featured_key) {
+            env.storage().persistent().remove(&featured_key);
+            let new_key = DataKey::Featured(new.clone());
+            env.storage().persistent().set(&new_key, &featured);
+            xpuni_common::extend_persistent(&env, &new_key);
+        }
+
+        env.events()
+            .publish((symbol_short!("migrate"), old, new), moved.len());
+
+        moved.len()
+    }
+
+    /// Retorna la dirección a la que se migró el estudiante `old`, si fue migrado.
+    pub fn migrated_to(env: Env, old: Address) -> Option<Address> {
+        xpuni_common::migrated_to(&env, &old)
+    }
+
+    // ── Borrado de datos personales ───────────────────────────────────────────
+
+    /// Reemplaza el `activity_title` de un badge por `[redacted]` y guarda solo su
+    /// SHA-256 (ver `redacted_hash`). IDs, puntos, fechas y `description_hash` no
+    /// cambian. Lo firma el admin, o el estudiante dueño junto con el admin. Los badges
+    /// de clase toman el título de la clase, que no es un dato personal, y los de modo
+    /// `Compact` no guardan el título.
+    pub fn redact(env: Env, caller: Address, badge_id: u64) {
+        let mut badge = match BADGES.get(&env, badge_id) {
+            Some(StoredBadge::Full(badge)) => badge,
+            Some(StoredBadge::Class(_) | StoredBadge::Compact(_)) => panic!("nothing_to_redact"),
+            None => panic!("badge_not_found"),
+        };
+        xpuni_common::require_redaction_auth(&env, &caller, &badge.student);
+        let key = DataKey::Redacted(badge_id);
+        if env.storage().persistent().has(&key) {
+            panic!("already_redacted");
+        }
+
+        let hash = xpuni_common::redact(&env, &mut badge.activity_title);
+        BADGES.replace(&env, badge_id, &StoredBadge::Full(badge));
+        env.storage().persistent().set(&key, &hash);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("redacted"), badge_id), hash);
+    }
+
+    /// SHA-256 del `activity_title` original de un badge borrado.
+    pub fn redacted_hash(env: Env, badge_id: u64) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::Redacted(badge_id))
+    }
+
+    // ── Clases de badge ───────────────────────────────────────────────────────
+
+    /// Crea una clase de badge. Con `requires_acceptance`, los badges que emiten los
+    /// revisores quedan como oferta hasta que el estudiante los acepta. Requiere el rol
+    /// `Admin`.
+    pub fn create_badge_class(
+        env: Env,
+        caller: Address,
+        class_id: u64,
+        title: String,
+        image_uri: String,
+        criteria_hash: BytesN<32>,
+        default_points: i128,
+        requires_acceptance: bool,
+    ) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        let key = DataKey::Class(class_id);
+        if env.storage().persistent().has(&key) {
+            panic!("class_already_exists");
+        }
+
+        let class = BadgeClass {
+            class_id,
+            title: title.clone(),
+            image_uri,
+            criteria_hash,
+            default_points,
+            active: true,
+            requires_acceptance,
+        };
+        env.storage().persistent().set(&key, &class);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("class_new"), class_id), title);
+    }
+
+    /// Activa o desactiva una clase. Requiere el rol `Admin`.
+    pub fn set_class_active(env: Env, caller: Address, class_id: u64, active: bool) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        let mut class = Self::get_badge_class(env.clone(), class_id);
+        class.active = active;
+        env.storage().persistent().set(&DataKey::Class(class_id), &class);
+
+        env.events()
+            .publish((symbol_short!("class_act"), class_id), active);
+    }
+
+    /// Retorna una clase de badge por su ID.
+    pub fn get_badge_class(env: Env, class_id: u64) -> BadgeClass {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Class(class_id))
+            .unwrap_or_else(|| panic!("class_not_found"))
+    }
+
+    /// Retorna hasta `limit` badge IDs emitidos desde `class_id` a partir de la posición
+    /// `start`, en orden de emisión.
+    pub fn badges_of_class(env: Env, class_id: u64, start: u32, limit: u32) -> Vec<u64> {
+        CLASS_BADGES.page(&env, &class_id, start, limit)
+    }
+
+    /// Retorna cuántos badges se emitieron desde `class_id`.
+    pub fn class_badge_count(env: Env, class_id: u64) -> u32 {
+        CLASS_BADGES.len(&env, &class_id)
+    }
+
+    /// Fija la plantilla SVG (hasta `MAX_TEMPLATE_LEN` bytes) con la que `token_uri`
+    /// dibuja los badges de la clase on-chain. Admite los marcadores `{{title}}`,
+    /// `{{student}}`, `{{date}}` y `{{badge_id}}`. Con `None`, los badges vuelven a
+    /// apuntar a `image_uri`. Requiere el rol `Admin`.
+    pub fn set_class_artwork(env: Env, caller: Address, class_id: u64, template: Option<String>) {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        Self::get_badge_class(env.clone(), class_id);
+        let key = DataKey::ClassArtwork(class_id);
+        match &template {
+            Some(template) => {
+                if template.len() > artwork::MAX_TEMPLATE_LEN {
+                    panic!("template_too_large");
+                }
+                env.storage().persistent().set(&key, template);
+                xpuni_common::extend_persistent(&env, &key);
+            }
+            None => env.storage().persistent().remove(&key),
+        }
+
+        env.events()
+            .publish((symbol_short!("class_art"), class_id), template.is_some());
+    }
+
+    /// Plantilla SVG de la clase, si tiene.
+    pub fn class_artwork(env: Env, class_id: u64) -> Option<String> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ClassArtwork(class_id))
+    }
+
+    // ── Interfaz no fungible ──────────────────────────────────────────────────
+    //
+    // Vista NFT de los badges para wallets y exploradores, según las propuestas de NFT
+    // de Stellar. El token_id es el badge_id. Los badges son soulbound: las funciones
+    // de transferencia y aprobación siempre fallan con `BadgeError::Soulbound`.
+
+    /// Nombre de la colección.
+    pub fn name(env: Env) -> String {
+        env.storage().instance().get(&DataKey::Name).unwrap()
+    }
+
+    /// Símbolo de la colección.
+    pub fn symbol(env: Env) -> String {
+        env.storage().instance().get(&DataKey::Symbol).unwrap()
+    }
+
+    /// Cantidad de badges de `owner`.
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        Self::student_badge_count(env, owner)
+    }
+
+    /// Estudiante dueño del badge `token_id`. Una oferta pendiente todavía no tiene dueño.
+    pub fn owner_of(env: Env, token_id: u64) -> Address {
+        Self::delivered_badge(&env, token_id).student
+    }
+
+    /// URI del badge `token_id`: su arte SVG on-chain como URI `data:` si su clase tiene
+    /// plantilla, o si no su `image_uri`. Falla en ofertas pendientes, como `owner_of`.
+    /// Los badges de `issue_badge` en modo `Compact` no guardan la imagen y retornan "".
+    pub fn token_uri(env: Env, token_id: u64) -> String {
+        let badge = Self::delivered_badge(&env, token_id);
+        let rendered = badge
+            .class_id
+            .and_then(|class_id| Self::class_artwork(env.clone(), class_id))
+            .and_then(|template| artwork::render(&env, &template, &badge));
+        rendered.unwrap_or(badge.image_uri)
+    }
+
+    pub fn transfer(
+        _env: Env,
+        _from: Address,
+        _to: Address,
+        _token_id: u64,
+    ) -> Result<(), BadgeError> {
+        Err(BadgeError::Soulbound)
+    }
+
+    pub fn transfer_from(
+        _env: Env,
+        _spender: Address,
+        _from: Address,
+        _to: Address,
+        _token_id: u64,
+    ) -> Result<(), BadgeError> {
+        Err(BadgeError::Soulbound)
+    }
+
+    pub fn approve(
+        _env: Env,
+        _approver: Address,
+        _approved: Address,
+        _token_id: u64,
+        _live_until_ledger: u32,
+    ) -> Result<(), BadgeError> {
+        Err(BadgeError::Soulbound)
+    }
+
+    pub fn approve_for_all(
+        _env: Env,
+        _owner: Address,
+        _operator: Address,
+        _live_until_ledger: u32,
+    ) -> Result<(), BadgeError> {
+        Err(BadgeError::Soulbound)
+    }
+
+    /// Siempre `None`: un badge no puede aprobarse.
+    pub fn get_approved(_env: Env, _token_id: u64) -> Option<Address> {
+        None
+    }
+
+    /// Siempre `false`: no hay operadores.
+    pub fn is_approved_for_all(_env: Env, _owner: Address, _operator: Address) -> bool {
+        false
+    }
+
+    // ── Helpers internos ──────────────────────────────────────────────────────
+
+    /// Exige la firma de `reviewer`, el rol `Reviewer` y que `activity_id` esté dentro de
+    /// su alcance, si tiene uno.
+    fn require_reviewer(env: &Env, reviewer: &Address, activity_id: u64) {
+        xpuni_access::require_role(env, reviewer, Role::Reviewer);
+        let scope: Option<Vec<u64>> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ReviewerScope(reviewer.clone()));
+        if let Some(scope) = scope {
+            if !scope.contains(activity_id) {
+                panic!("activity_out_of_scope");
+            }
+        }
+    }
+
+    /// Entrega un badge ya registrado: lo agrega a los índices de su estudiante, clase y
+    /// revisor, publica la emisión (el evento propio que lee el backend y el evento
+    /// `mint` estándar de la interfaz no fungible) y avanza las rutas del estudiante.
+    fn deliver(
+        env: &Env,
+        badge_id: u64,
+        reviewer: &Address,
+        student: Address,
+        class_id: Option<u64>,
+        activity_id: u64,
+        issued_at: u64,
+    ) {
+        BADGES.add_to_owner(env, &student, badge_id);
+        DAY_BADGES.push(env, &(issued_at / DAY), badge_id);
+        if let Some(class_id) = class_id {
+            CLASS_BADGES.push(env, &class_id, badge_id);
+        }
+        REVIEWER_BADGES.push(env, reviewer, badge_id);
+        if activity_id != NO_ACTIVITY {
+            ACTIVITY_BADGES.push(env, &activity_id, badge_id);
+        }
+
+        env.events()
+            .publish((symbol_short!("badge"), student.clone(), badge_id), badge_id);
+        env.events().publish((symbol_short!("mint"), student.clone()), badge_id);
+
+        let met = Self::requirements_met(env, class_id, activity_id);
+        Self::advance_paths(env, &student, &met);
+    }
+
+    /// Requisitos de ruta que cumple un badge de `class_id` para `activity_id`.
+    fn requirements_met(env: &Env, class_id: Option<u64>, activity_id: u64) -> Vec<Requirement> {
+        let mut met = Vec::new(env);
+        met.push_back(Requirement::Activity(activity_id));
+        if let Some(class_id) = class_id {
+            met.push_back(Requirement::Class(class_id));
+        }
+        met
+    }
+
+    /// Pasa el badge de `old` a `new`, con su entrada en `BadgeFor` salvo que `new` ya
+    /// tenga un badge no revocado de esa actividad, que sigue siendo el que cuenta.
+    /// Retorna los requisitos de ruta que cumple.
+    fn reassign_badge(env: &Env, badge_id: u64, old: &Address, new: &Address) -> Vec<Requirement> {
+        let (stored, class_id, activity_id) = match BADGES.get(env, badge_id).unwrap() {
+            StoredBadge::Full(mut badge) => {
+                badge.student = new.clone();
+                let activity_id = badge.activity_id;
+                (StoredBadge::Full(badge), None, activity_id)
+            }
+            StoredBadge::Class(mut badge) => {
+                badge.student = new.clone();
+                let (class_id, activity_id) = (badge.class_id, badge.activity_id);
+                (StoredBadge::Class(badge), Some(class_id), activity_id)
+            }
+            StoredBadge::Compact(mut badge) => {
+                badge.student = new.clone();
+                let activity_id = badge.activity_id;
+                (StoredBadge::Compact(badge), None, activity_id)
+            }
+        };
+        BADGES.replace(env, badge_id, &stored);
+
+        let old_key = DataKey::BadgeFor(old.clone(), activity_id);
+        if env.storage().persistent().get(&old_key) == Some(badge_id) {
+            env.storage().persistent().remove(&old_key);
+            let current = Self::badge_for(env.clone(), new.clone(), activity_id);
+            let current_live = current.is_some_and(|id| {
+                !env.storage().persistent().has(&DataKey::Revocation(id))
+            });
+            if !current_live {
+                Self::set_badge_for(env, new, activity_id, badge_id);
+            }
+        }
+        Self::requirements_met(env, class_id, activity_id)
+    }
+
+    /// Exige la firma de `caller` y que sea un `Admin` o el revisor del badge.
+    fn require_admin_or_reviewer(env: &Env, caller: &Address, badge: &BadgeData) {
+        caller.require_auth();
+        if *caller != badge.reviewer && !xpuni_access::has_role(env, Role::Admin, caller) {
+            panic!("missing_role");
+        }
+    }
+
+    /// Emite un badge sin clase a `student`, ya validado el revisor. En modo `Compact` el
+    /// llamador publica el título.
+    fn issue_single(
+        env: &Env,
+        reviewer: &Address,
+        student: Address,
+        activity_id: u64,
+        content: BadgeContent,
+        points_awarded: i128,
+        description_hash: BytesN<32>,
+    ) -> u64 {
+        Self::require_unique(env, &student, activity_id);
+
+        let compact = xpuni_common::storage_mode(env) == StorageMode::Compact;
+        let badge_id = BADGES.insert(env, |badge_id| {
+            if compact {
+                StoredBadge::Compact(CompactBadge {
+                    student: student.clone(),
+                    activity_id,
+                    reviewer: reviewer.clone(),
+                    points_awarded,
+                    issued_at: env.ledger().timestamp(),
+                    description_hash,
+                    content_hash: xpuni_common::content_hash(env, &content),
+                })
+            } else {
+                StoredBadge::Full(BadgeData {
+                    badge_id,
+                    class_id: None,
+                    student: student.clone(),
+                    activity_id,
+                    activity_title: content.activity_title.clone(),
+                    image_uri: content.image_uri.clone(),
+                    reviewer: reviewer.clone(),
+                    points_awarded,
+                    issued_at: env.ledger().timestamp(),
+                    description_hash,
+                })
+            }
+        });
+
+        Self::set_badge_for(env, &student, activity_id, badge_id);
+        let issued_at = env.ledger().timestamp();
+        Self::deliver(env, badge_id, reviewer, student, None, activity_id, issued_at);
+
+        badge_id
+    }
+
+    /// Reparte `total` en proporción a `shares` por el método del mayor resto: parte
+    /// entera para cada uno y el sobrante de a un punto a las mayores fracciones
+    /// descartadas, con empate a favor del primero.
+    fn split_points(env: &Env, total: i128, shares: &Vec<u32>) -> Vec<i128> {
+        let sum: i128 = shares.iter().map(i128::from).sum();
+        if sum == 0 {
+            panic!("invalid_shares");
+        }
+        let mut points = Vec::new(env);
+        let mut fractions = Vec::new(env);
+        for share in shares.iter() {
+            let scaled = total * i128::from(share);
+            points.push_back(scaled / sum);
+            fractions.push_back(scaled % sum);
+        }
+
+        let mut remainder = total - points.iter().sum::<i128>();
+        while remainder > 0 {
+            let mut best = 0;
+            for i in 1..fractions.len() {
+                if fractions.get_unchecked(i) > fractions.get_unchecked(best) {
+                    best = i;
+                }
+            }
+            points.set(best, points.get_unchecked(best) + 1);
+            fractions.set(best, -1);
+            remainder -= 1;
+        }
+        points
+    }
+
+    /// `query_badges` sin actividad ni revisor: recorre los días del rango en orden.
+    fn query_by_day(
+        env: &Env,
+        query: &BadgeQuery,
+        mut cursor: QueryCursor,
+        mut budget: u32,
+    ) -> (Vec<u64>, Option<QueryCursor>) {
+        let mut badge_ids = Vec::new(env);
+        let last_day = query.to.saturating_sub(1) / DAY;
+        let pending = |cursor: &QueryCursor| query.from < query.to && cursor.day <= last_day;
+
+        while budget > 0 && pending(&cursor) {
+            let page = DAY_BADGES.page(env, &cursor.day, cursor.position, budget);
+            for badge_id in page.iter() {
+                if Self::matches_query(env, query, badge_id) {
+                    badge_ids.push_back(badge_id);
+                }
+            }
+            budget -= page.len().max(1);
+            cursor.position += page.len();
+            if cursor.position >= DAY_BADGES.len(env, &cursor.day) {
+                cursor.day += 1;
+                cursor.position = 0;
+            }
+        }
+        let next = pending(&cursor).then_some(cursor);
+        (badge_ids, next)
+    }
+
+    fn matches_query(env: &Env, query: &BadgeQuery, badge_id: u64) -> bool {
+        let badge = Self::get_badge(env.clone(), badge_id);
+        badge.issued_at >= query.from
+            && badge.issued_at < query.to
+            && query.activity_id.is_none_or(|id| id == badge.activity_id)
+            && query.reviewer.as_ref().is_none_or(|r| *r == badge.reviewer)
+    }
+
+    /// Falla si `badge_id` no es un badge entregado a `student`.
+    fn require_owned(env: &Env, student: &Address, badge_id: u64) {
+        if Self::delivered_badge(env, badge_id).student != *student {
+            panic!("not_badge_owner");
+        }
+    }
+
+    /// Retorna `badge_id` si ya fue entregado. Una oferta pendiente no pertenece a nadie.
+    fn delivered_badge(env: &Env, badge_id: u64) -> BadgeData {
+        let badge = Self::load_badge(env, badge_id).unwrap_or_else(|| panic!("badge_not_found"));
+        if Self::pending_offer(env.clone(), badge_id).is_some() {
+            panic!("not_badge_owner");
+        }
+        badge
+    }
+
+    fn set_valid_until(env: &Env, badge_id: u64, valid_until: u64) {
+        let key = DataKey::ValidUntil(badge_id);
+        env.storage().persistent().set(&key, &valid_until);
+        xpuni_common::extend_persistent(env, &key);
+    }
+
+    fn is_expired(env: &Env, badge_id: u64) -> bool {
+        match Self::valid_until(env.clone(), badge_id) {
+            Some(valid_until) => env.ledger().timestamp() > valid_until,
+            None => false,
+        }
+    }
+
+    /// Falla si `student` ya tiene un badge no revocado de `activity_id` (entregado o
+    /// pendiente) y la actividad no es repetible.
+    fn require_unique(env: &Env, student: &Address, activity_id: u64) {
+        if activity_id == NO_ACTIVITY || Self::activity_repeatable(env.clone(), activity_id) {
+            return;
+        }
+        let Some(existing) = Self::badge_for(env.clone(), student.clone(), activity_id) else {
+            return;
+        };
+        if env.storage().persistent().has(&DataKey::Revocation(existing)) {
+            return;
+        }
+        // Una oferta vencida sin respuesta no bloquea: se descarta como si se rechazara.
+        if let Some(expires_at) = Self::pending_offer(env.clone(), existing) {
+            if env.ledger().timestamp() > expires_at {
+                Self::discard_offer(env, existing, student, activity_id);
+                env.events()
+                    .publish((symbol_short!("offer_exp"), student.clone(), existing), existing);
+                return;
+            }
+        }
+        panic!("duplicate_badge");
+    }
+
+    /// Borra una oferta pendiente y su badge.
+    fn discard_offer(env: &Env, badge_id: u64, student: &Address, activity_id: u64) {
+        env.storage().persistent().remove(&DataKey::Offer(badge_id));
+        BADGES.remove(env, badge_id);
+        let for_key = DataKey::BadgeFor(student.clone(), activity_id);
+        if env.storage().persistent().get(&for_key) == Some(badge_id) {
+            env.storage().persistent().remove(&for_key);
+        }
+    }
+
+    fn set_badge_for(env: &Env, student: &Address, activity_id: u64, badge_id: u64) {
+        if activity_id == NO_ACTIVITY {
+            return;
+        }
+        let key = DataKey::BadgeFor(student.clone(), activity_id);
+        env.storage().persistent().set(&key, &badge_id);
+        xpuni_common::extend_persistent(env, &key);
+    }
+
+    /// Cuenta un badge nuevo que cumple `met` en cada ruta que incluya alguno de esos
+    /// requisitos, y emite el meta-badge de las que queden completas. Las rutas cuyo
+    /// meta-badge está en una clase desactivada quedan cerradas y no avanzan.
+    fn advance_paths(env: &Env, student: &Address, met: &Vec<Requirement>) {
+        let mut path_ids: Vec<u64> = Vec::new(env);
+        for requirement in met.iter() {
+            let ids: Vec<u64> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::PathsFor(requirement))
+                .unwrap_or_else(|| Vec::new(env));
+            for path_id in ids.iter() {
+                if !path_ids.contains(path_id) {
+                    path_ids.push_back(path_id);
+                }
+            }
+        }
+
+        for path_id in path_ids.iter() {
+            let mut progress = Self::path_progress(env.clone(), student.clone(), path_id);
+            let path = Self::get_path(env.clone(), path_id);
+            let meta_class = Self::get_badge_class(env.clone(), path.meta_class_id);
+            if progress.completed || !meta_class.active {
+                continue;
+            }
+
+            progress.matched_badges += 1;
+            for requirement in met.iter() {
+                if path.requirements.contains(&requirement)
+                    && !progress.satisfied.contains(&requirement)
+                {
+                    progress.satisfied.push_back(requirement);
+                }
+            }
+            progress.completed = Self::path_complete(&path, &progress);
+            Self::store_progress(env, student, &path, &meta_class, progress);
+        }
+    }
+
+    fn path_complete(path: &LearningPath, progress: &PathProgress) -> bool {
+        if path.threshold > 0 {
+            progress.matched_badges >= path.threshold
+        } else {
+            progress.satisfied.len() == path.requirements.len()
+        }
+    }
+
+    /// Guarda el avance de `student` y, si la ruta quedó completa sin meta-badge, se lo
+    /// emite.
+    fn store_progress(
+        env: &Env,
+        student: &Address,
+        path: &LearningPath,
+        meta_class: &BadgeClass,
+        mut progress: PathProgress,
+    ) {
+        // Se guarda antes de emitir el meta-badge: su entrega vuelve a pasar por
+        // `advance_paths` y la ruta ya debe figurar como completa.
+        let key = DataKey::Progress(student.clone(), path.path_id);
+        env.storage().persistent().set(&key, &progress);
+        xpuni_common::extend_persistent(env, &key);
+
+        if progress.completed && progress.meta_badge_id.is_none() {
+            let meta_badge_id = Self::issue_from_class(
+                env,
+                env.current_contract_address(),
+                student.clone(),
+                path.meta_class_id,
+                NO_ACTIVITY,
+                meta_class.criteria_hash.clone(),
+                false,
+            );
+            progress.meta_badge_id = Some(meta_badge_id);
+            env.storage().persistent().set(&key, &progress);
+
+            env.events().publish(
+                (symbol_short!("path_done"), student.clone(), path.path_id),
+                meta_badge_id,
+            );
+        }
+    }
+
+    /// Emite un badge de una clase activa sin comprobar permisos: lo usan
+    /// `issue_class_badge` y `claim`, que ya autorizaron la emisión. Con `offer`, si la
+    /// clase requiere aceptación, el badge queda pendiente en vez de entregarse.
+    fn issue_from_class(
+        env: &Env,
+        reviewer: Address,
+        student: Address,
+        class_id: u64,
+        activity_id: u64,
+        description_hash: BytesN<32>,
+        offer: bool,
+    ) -> u64 {
+        let class = Self::get_badge_class(env.clone(), class_id);
+        if !class.active {
+            panic!("class_inactive");
+        }
+        if let Some(path_id) = Self::class_prerequisite(env.clone(), class_id) {
+            if !Self::path_progress(env.clone(), student.clone(), path_id).completed {
+                panic!("prerequisites_not_met");
+            }
+        }
+        Self::require_unique(env, &student, activity_id);
+
+        let badge_id = BADGES.insert(env, |_| {
+            StoredBadge::Class(ClassBadge {
+                class_id,
+                student: student.clone(),
+                activity_id,
+                reviewer: reviewer.clone(),
+                issued_at: env.ledger().timestamp(),
+                description_hash,
+            })
+        });
+        Self::set_badge_for(env, &student, activity_id, badge_id);
+        let validity: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ClassValidity(class_id));
+        if let Some(secs) = validity {
+            Self::set_valid_until(env, badge_id, env.ledger().timestamp() + secs);
+        }
+
+        if offer && class.requires_acceptance {
+            let expires_at = env.ledger().timestamp() + OFFER_WINDOW;
+            let key = DataKey::Offer(badge_id);
+            env.storage().persistent().set(&key, &expires_at);
+            xpuni_common::extend_persistent(env, &key);
+            STUDENT_OFFERS.push(env, &student, badge_id);
+            env.events()
+                .publish((symbol_short!("offer"), student, badge_id), expires_at);
+        } else {
+            let issued_at = env.ledger().timestamp();
+            Self::deliver(
+                env,
+                badge_id,
+                &reviewer,
+                student,
+                Some(class_id),
+                activity_id,
+                issued_at,
+            );
+        }
+
+        badge_id
+    }
+
+    fn leaf_hash(env: &Env, leaf: &BatchLeaf) -> BytesN<32> {
+        env.crypto().sha256(&leaf.clone().to_xdr(env)).to_bytes()
+    }
+
+    /// Nodo padre de dos hashes, ordenándolos para que la prueba no dependa del lado.
+    fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+        let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+        let mut data = Bytes::from_array(env, &lo.to_array());
+        data.append(&Bytes::from_array(env, &hi.to_array()));
+        env.crypto().sha256(&data).to_bytes()
+    }
+
+    /// Lee un badge y, si se emitió desde una clase, completa los campos de la clase.
+    fn load_badge(env: &Env, badge_id: u64) -> Option<BadgeData> {
+        let badge = match BADGES.get(env, badge_id)? {
+            StoredBadge::Full(badge) => badge,
+            StoredBadge::Compact(badge) => BadgeData {
+                badge_id,
+                class_id: None,
+                student: badge.student,
+                activity_id: badge.activity_id,
+                activity_title: String::from_str(env, ""),
+                image_uri: String::from_str(env, ""),
+                reviewer: badge.reviewer,
+                points_awarded: badge.points_awarded,
+                issued_at: badge.issued_at,
+                description_hash: badge.description_hash,
+            },
+            StoredBadge::Class(badge) => {
+                let class = Self::get_badge_class(env.clone(), badge.class_id);
+                BadgeData {
+                    badge_id,
+                    class_id: Some(badge.class_id),
+                    student: badge.student,
+                    activity_id: badge.activity_id,
+                    activity_title: class.title,
+                    image_uri: class.image_uri,
+                    reviewer: badge.reviewer,
+                    points_awarded: class.default_points,
+                    issued_at: badge.issued_at,
+                    description_hash: badge.description_hash,
+                }
+            }
+        };
+        Some(badge)
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl AccessControl for AchievementBadges {}
+
+#[contractimpl(contracttrait)]
+impl Pausable for AchievementBadges {}
+
+impl TimelockHook for AchievementBadges {
+    fn apply_action(env: &Env, action: TimelockAction) {
+        match action {
+            TimelockAction::SetAdmin(new_admin) => {
+                xpuni_access::transfer_admin(env, &xpuni_common::admin(env), &new_admin);
+                xpuni_common::set_admin(env, &new_admin);
+            }
+            _ => panic!("unsupported_action"),
+        }
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl Timelocked for AchievementBadges {}
 
 mod test;
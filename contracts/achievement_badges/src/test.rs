@@ -1,44 +1,49 @@
 #![cfg(test)]
+extern crate std;
 use super::*;
-use soroban_sdk::{testutils::Address as _, BytesN, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Events as _, Ledger},
+    vec, xdr, Bytes, BytesN, Env, String,
+};
 
-fn setup(env: &Env) -> (AchievementBadgesClient, Address) {
-    let contract_id = env.register(AchievementBadges, ());
-    let client = AchievementBadgesClient::new(env, &contract_id);
+fn setup(env: &Env) -> (AchievementBadgesClient<'_>, Address) {
     let admin = Address::generate(env);
-    client.initialize(&admin);
+    let contract_id = env.register(
+        AchievementBadges,
+        (
+            &admin,
+            String::from_str(env, "XPUni Badges"),
+            String::from_str(env, "XPB"),
+            None::<Address>,
+            StorageMode::Full,
+        ),
+    );
+    let client = AchievementBadgesClient::new(env, &contract_id);
     (client, admin)
 }
 
 #[test]
-fn test_initialize() {
+fn test_constructor() {
     let env = Env::default();
     env.mock_all_auths();
     let (client, admin) = setup(&env);
 
     assert_eq!(client.admin(), admin);
     assert_eq!(client.badge_count(), 0);
-}
-
-#[test]
-#[should_panic(expected = "already_initialized")]
-fn test_double_initialize_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let (client, admin) = setup(&env);
-    client.initialize(&admin);
+    assert_eq!(client.points_contract(), None);
 }
 
 #[test]
 fn test_issue_and_get_badge() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let student = Address::generate(&env);
     let hash = BytesN::from_array(&env, &[0u8; 32]);
 
     let badge_id = client.issue_badge(
+        &admin,
         &student,
         &1_u64,
         &String::from_str(&env, "Taller de Robotica"),
@@ -62,12 +67,13 @@ fn test_issue_and_get_badge() {
 fn test_multiple_badges_increment_correctly() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let student = Address::generate(&env);
     let hash = BytesN::from_array(&env, &[0u8; 32]);
 
     let id0 = client.issue_badge(
+        &admin,
         &student,
         &1_u64,
         &String::from_str(&env, "Actividad A"),
@@ -76,6 +82,7 @@ fn test_multiple_badges_increment_correctly() {
         &hash,
     );
     let id1 = client.issue_badge(
+        &admin,
         &student,
         &2_u64,
         &String::from_str(&env, "Actividad B"),
@@ -93,19 +100,43 @@ fn test_multiple_badges_increment_correctly() {
 fn test_get_student_badges() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let s1 = Address::generate(&env);
     let s2 = Address::generate(&env);
     let hash = BytesN::from_array(&env, &[0u8; 32]);
 
     let img = String::from_str(&env, "https://universidad.edu/badges/default.png");
-    client.issue_badge(&s1, &1_u64, &String::from_str(&env, "Act A"), &img, &50_i128, &hash);
-    client.issue_badge(&s2, &2_u64, &String::from_str(&env, "Act B"), &img, &60_i128, &hash);
-    client.issue_badge(&s1, &3_u64, &String::from_str(&env, "Act C"), &img, &70_i128, &hash);
+    client.issue_badge(
+        &admin,
+        &s1,
+        &1_u64,
+        &String::from_str(&env, "Act A"),
+        &img,
+        &50_i128,
+        &hash,
+    );
+    client.issue_badge(
+        &admin,
+        &s2,
+        &2_u64,
+        &String::from_str(&env, "Act B"),
+        &img,
+        &60_i128,
+        &hash,
+    );
+    client.issue_badge(
+        &admin,
+        &s1,
+        &3_u64,
+        &String::from_str(&env, "Act C"),
+        &img,
+        &70_i128,
+        &hash,
+    );
 
-    let s1_badges = client.get_student_badges(&s1);
-    let s2_badges = client.get_student_badges(&s2);
+    let s1_badges = client.get_student_badges(&s1, &0, &10);
+    let s2_badges = client.get_student_badges(&s2, &0, &10);
 
     assert_eq!(s1_badges.len(), 2);
     assert_eq!(s2_badges.len(), 1);
@@ -121,10 +152,37 @@ fn test_get_student_badges_empty() {
     let (client, _) = setup(&env);
 
     let student = Address::generate(&env);
-    let badges = client.get_student_badges(&student);
+    let badges = client.get_student_badges(&student, &0, &10);
     assert_eq!(badges.len(), 0);
 }
 
+#[test]
+fn test_get_student_badges_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+    let img = String::from_str(&env, "https://universidad.edu/badges/default.png");
+    for activity_id in 0..5_u64 {
+        client.issue_badge(
+            &admin,
+            &student,
+            &activity_id,
+            &String::from_str(&env, "Taller"),
+            &img,
+            &10_i128,
+            &hash,
+        );
+    }
+
+    assert_eq!(client.student_badge_count(&student), 5);
+    assert_eq!(client.get_student_badges(&student, &1, &2), vec![&env, 1_u64, 2_u64]);
+    assert_eq!(client.get_student_badges(&student, &4, &10), vec![&env, 4_u64]);
+    assert_eq!(client.get_student_badges(&student, &5, &10).len(), 0);
+}
+
 #[test]
 #[should_panic(expected = "badge_not_found")]
 fn test_get_nonexistent_badge() {
@@ -133,3 +191,1679 @@ fn test_get_nonexistent_badge() {
     let (client, _) = setup(&env);
     client.get_badge(&999_u64);
 }
+
+#[test]
+fn test_set_admin_through_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin.clone()), &delay);
+    assert_eq!(client.pending_actions().len(), 1);
+    assert_eq!(client.admin(), admin);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+
+    assert_eq!(client.admin(), new_admin);
+    assert_eq!(client.pending_actions().len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "unsupported_action")]
+fn test_exchange_rate_rejected_when_queued() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let action = TimelockAction::SetExchangeRate(
+        Address::generate(&env),
+        Address::generate(&env),
+        1_000_000,
+    );
+
+    client.queue_action(&admin, &action, &client.min_delay());
+}
+
+#[test]
+fn test_set_admin_moves_retained_roles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+    let pauser = Address::generate(&env);
+
+    // El admin delega `Pauser` y renuncia a él; el resto lo conserva.
+    client.grant_role(&admin, &Role::Pauser, &pauser);
+    client.renounce_role(&admin, &Role::Pauser);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin.clone()), &delay);
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+
+    for role in [Role::Admin, Role::Minter, Role::Reviewer, Role::Partner, Role::Upgrader] {
+        assert!(client.has_role(&role, &new_admin));
+        assert!(!client.has_role(&role, &admin));
+    }
+    assert!(!client.has_role(&Role::Pauser, &new_admin));
+    assert!(client.has_role(&Role::Pauser, &pauser));
+}
+
+#[test]
+fn test_delegated_reviewer_is_recorded() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    let badge_id = client.issue_badge(
+        &reviewer,
+        &student,
+        &1_u64,
+        &String::from_str(&env, "Taller de Robotica"),
+        &String::from_str(&env, "https://universidad.edu/badges/robotica.png"),
+        &100_i128,
+        &hash,
+    );
+
+    assert_eq!(client.get_badge(&badge_id).reviewer, reviewer);
+}
+
+#[test]
+#[should_panic(expected = "missing_role")]
+fn test_issue_badge_requires_reviewer_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, _) = setup(&env);
+    let outsider = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    client.issue_badge(
+        &outsider,
+        &Address::generate(&env),
+        &1_u64,
+        &String::from_str(&env, "Taller de Robotica"),
+        &String::from_str(&env, "https://universidad.edu/badges/robotica.png"),
+        &100_i128,
+        &hash,
+    );
+}
+
+fn issue(
+    env: &Env,
+    client: &AchievementBadgesClient,
+    reviewer: &Address,
+    student: &Address,
+) -> u64 {
+    client.issue_badge(
+        reviewer,
+        student,
+        &1_u64,
+        &String::from_str(env, "Taller de Robotica"),
+        &String::from_str(env, "https://universidad.edu/badges/robotica.png"),
+        &100_i128,
+        &BytesN::from_array(env, &[0u8; 32]),
+    )
+}
+
+#[test]
+fn test_reviewer_revokes_own_badge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+    let student = Address::generate(&env);
+    let reason = BytesN::from_array(&env, &[7u8; 32]);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    let badge_id = issue(&env, &client, &reviewer, &student);
+    assert!(client.is_valid(&badge_id));
+
+    client.revoke_badge(&reviewer, &badge_id, &reason, &false);
+
+    assert!(!client.is_valid(&badge_id));
+    let revocation = client.get_revocation(&badge_id).unwrap();
+    assert_eq!(revocation.revoked_by, reviewer);
+    assert_eq!(revocation.reason_hash, reason);
+    assert_eq!(revocation.points_clawed_back, 0);
+    // El badge sigue consultable y en el índice del estudiante.
+    assert_eq!(client.get_badge(&badge_id).student, student);
+    assert_eq!(client.student_badge_count(&student), 1);
+    assert!(!client.is_valid(&99_u64));
+}
+
+#[test]
+#[should_panic(expected = "missing_role")]
+fn test_revoke_badge_requires_admin_or_reviewer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let other_reviewer = Address::generate(&env);
+
+    client.grant_role(&admin, &Role::Reviewer, &other_reviewer);
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    client.revoke_badge(
+        &other_reviewer,
+        &badge_id,
+        &BytesN::from_array(&env, &[7u8; 32]),
+        &false,
+    );
+}
+
+#[test]
+#[should_panic(expected = "badge_already_revoked")]
+fn test_revoke_badge_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reason = BytesN::from_array(&env, &[7u8; 32]);
+
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    client.revoke_badge(&admin, &badge_id, &reason, &false);
+    client.revoke_badge(&admin, &badge_id, &reason, &false);
+}
+
+#[test]
+fn test_revoke_badge_claws_back_points() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let points_id = env.register(
+        school_points::SchoolPoints,
+        (
+            &admin,
+            String::from_str(&env, "XP Points"),
+            String::from_str(&env, "XP"),
+        ),
+    );
+    let points = school_points::SchoolPointsClient::new(&env, &points_id);
+    let badges_id = env.register(
+        AchievementBadges,
+        (
+            &admin,
+            String::from_str(&env, "XPUni Badges"),
+            String::from_str(&env, "XPB"),
+            Some(points_id.clone()),
+            StorageMode::Full,
+        ),
+    );
+    let client = AchievementBadgesClient::new(&env, &badges_id);
+    let student = Address::generate(&env);
+
+    points.grant_role(&admin, &Role::Partner, &badges_id);
+    let badge_id = issue(&env, &client, &admin, &student);
+    points.mint(&admin, &student, &130_i128, &symbol_short!("academic"));
+
+    client.revoke_badge(&admin, &badge_id, &BytesN::from_array(&env, &[7u8; 32]), &true);
+
+    assert_eq!(points.balance(&student), 30);
+    assert_eq!(client.get_revocation(&badge_id).unwrap().points_clawed_back, 100);
+}
+
+fn create_class(env: &Env, client: &AchievementBadgesClient, admin: &Address, class_id: u64) {
+    client.create_badge_class(
+        admin,
+        &class_id,
+        &String::from_str(env, "Hackathon 2025"),
+        &String::from_str(env, "ipfs://QmHackathon"),
+        &BytesN::from_array(env, &[3u8; 32]),
+        &250_i128,
+        &false,
+    );
+}
+
+#[test]
+fn test_issue_class_badge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    create_class(&env, &client, &admin, 7);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+    let badge_id = client.issue_class_badge(&admin, &student, &7_u64, &42_u64, &hash);
+
+    let badge = client.get_badge(&badge_id);
+    assert_eq!(badge.class_id, Some(7));
+    assert_eq!(badge.activity_id, 42);
+    assert_eq!(badge.activity_title, String::from_str(&env, "Hackathon 2025"));
+    assert_eq!(badge.image_uri, String::from_str(&env, "ipfs://QmHackathon"));
+    assert_eq!(badge.points_awarded, 250);
+    assert_eq!(client.get_student_badges(&student, &0, &10), vec![&env, badge_id]);
+    assert!(client.is_valid(&badge_id));
+}
+
+#[test]
+fn test_badges_of_class_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    create_class(&env, &client, &admin, 1);
+    create_class(&env, &client, &admin, 2);
+    for i in 0..4_u64 {
+        let class_id = if i % 2 == 0 { 1_u64 } else { 2_u64 };
+        client.issue_class_badge(&admin, &Address::generate(&env), &class_id, &i, &hash);
+    }
+    // Los badges sueltos no entran en ningún índice de clase.
+    issue(&env, &client, &admin, &Address::generate(&env));
+
+    assert_eq!(client.class_badge_count(&1_u64), 2);
+    assert_eq!(client.badges_of_class(&1_u64, &0, &10), vec![&env, 0_u64, 2_u64]);
+    assert_eq!(client.badges_of_class(&2_u64, &1, &10), vec![&env, 3_u64]);
+    assert_eq!(client.get_badge(&4_u64).class_id, None);
+}
+
+#[test]
+#[should_panic(expected = "class_inactive")]
+fn test_inactive_class_cannot_issue() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    create_class(&env, &client, &admin, 7);
+    client.set_class_active(&admin, &7_u64, &false);
+    assert!(!client.get_badge_class(&7_u64).active);
+
+    client.issue_class_badge(
+        &admin,
+        &Address::generate(&env),
+        &7_u64,
+        &42_u64,
+        &BytesN::from_array(&env, &[0u8; 32]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "class_already_exists")]
+fn test_create_badge_class_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    create_class(&env, &client, &admin, 7);
+    create_class(&env, &client, &admin, 7);
+}
+
+#[test]
+fn test_non_fungible_interface() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    issue_activity(&env, &client, &admin, &student, 2);
+    let badge_id = issue(&env, &client, &admin, &student);
+
+    assert_eq!(client.name(), String::from_str(&env, "XPUni Badges"));
+    assert_eq!(client.symbol(), String::from_str(&env, "XPB"));
+    assert_eq!(client.balance(&student), 2);
+    assert_eq!(client.balance(&Address::generate(&env)), 0);
+    assert_eq!(client.owner_of(&badge_id), student);
+    assert_eq!(
+        client.token_uri(&badge_id),
+        String::from_str(&env, "https://universidad.edu/badges/robotica.png")
+    );
+    assert_eq!(client.get_approved(&badge_id), None);
+}
+
+/// Decodifica la URI `data:image/svg+xml;base64,...` que retorna `token_uri`.
+fn decode_svg(uri: &String) -> std::string::String {
+    let mut buf = std::vec![0u8; uri.len() as usize];
+    uri.copy_into_slice(&mut buf);
+    let encoded = buf.strip_prefix(b"data:image/svg+xml;base64,").unwrap();
+
+    let alphabet = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let (mut bits, mut n, mut svg) = (0u32, 0, std::vec::Vec::new());
+    for c in encoded.iter().filter(|&&c| c != b'=') {
+        let value = alphabet.iter().position(|a| a == c).unwrap() as u32;
+        bits = ((bits << 6) | value) & 0xffff;
+        n += 6;
+        if n >= 8 {
+            n -= 8;
+            svg.push((bits >> n) as u8);
+        }
+    }
+    std::string::String::from_utf8(svg).unwrap()
+}
+
+#[test]
+fn test_token_uri_renders_class_artwork() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.create_badge_class(
+        &admin,
+        &7_u64,
+        &String::from_str(&env, "R&D <Lab>"),
+        &String::from_str(&env, "ipfs://QmLab"),
+        &BytesN::from_array(&env, &[3u8; 32]),
+        &250_i128,
+        &false,
+    );
+    let template = "<svg><text>{{title}}</text><text>{{student}}</text>\
+                    <text>{{date}}</text><text>#{{badge_id}} {{other}}</text></svg>";
+    client.set_class_artwork(&admin, &7_u64, &Some(String::from_str(&env, template)));
+    // 2025-01-01T13:00:00Z
+    env.ledger().with_mut(|l| l.timestamp = 1_735_736_400);
+
+    issue(&env, &client, &admin, &student);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+    let badge_id = client.issue_class_badge(&admin, &student, &7_u64, &42_u64, &hash);
+
+    let mut strkey = [0u8; 56];
+    student.to_string().copy_into_slice(&mut strkey);
+    let strkey = std::str::from_utf8(&strkey).unwrap();
+    let expected = std::format!(
+        "<svg><text>R&amp;D &lt;Lab&gt;</text><text>{}…{}</text>\
+         <text>2025-01-01</text><text>#1 {{{{other}}}}</text></svg>",
+        &strkey[..4],
+        &strkey[52..]
+    );
+    assert_eq!(decode_svg(&client.token_uri(&badge_id)), expected);
+
+    // Sin plantilla, vuelve a la imagen de la clase.
+    client.set_class_artwork(&admin, &7_u64, &None);
+    assert_eq!(client.token_uri(&badge_id), String::from_str(&env, "ipfs://QmLab"));
+}
+
+#[test]
+#[should_panic(expected = "template_too_large")]
+fn test_class_artwork_too_large() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    create_class(&env, &client, &admin, 7);
+    let template = String::from_bytes(&env, &[b'a'; 4_097]);
+    client.set_class_artwork(&admin, &7_u64, &Some(template));
+}
+
+#[test]
+fn test_badges_are_soulbound() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+
+    assert_eq!(
+        client.try_transfer(&student, &other, &badge_id),
+        Err(Ok(BadgeError::Soulbound))
+    );
+    assert_eq!(
+        client.try_transfer_from(&other, &student, &other, &badge_id),
+        Err(Ok(BadgeError::Soulbound))
+    );
+    assert_eq!(
+        client.try_approve(&student, &other, &badge_id, &1000_u32),
+        Err(Ok(BadgeError::Soulbound))
+    );
+    assert_eq!(
+        client.try_approve_for_all(&student, &other, &1000_u32),
+        Err(Ok(BadgeError::Soulbound))
+    );
+    assert_eq!(client.owner_of(&badge_id), student);
+}
+
+#[test]
+fn test_migrate_student_moves_badges() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    create_class(&env, &client, &admin, 7);
+    let first = issue(&env, &client, &admin, &old);
+    let second = client.issue_class_badge(&admin, &old, &7_u64, &42_u64, &hash);
+    let existing = issue(&env, &client, &admin, &new);
+
+    assert_eq!(client.migrate_student(&admin, &old, &new), 2);
+
+    assert_eq!(client.student_badge_count(&old), 0);
+    assert_eq!(
+        client.get_student_badges(&new, &0, &10),
+        vec![&env, existing, first, second]
+    );
+    assert_eq!(client.owner_of(&first), new);
+    assert_eq!(client.get_badge(&second).student, new);
+    assert_eq!(client.migrated_to(&old), Some(new.clone()));
+    assert_eq!(client.migrated_to(&new), None);
+}
+
+#[test]
+#[should_panic(expected = "missing_role")]
+fn test_migrate_student_requires_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    client.migrate_student(&reviewer, &Address::generate(&env), &Address::generate(&env));
+}
+
+#[test]
+fn test_verify_evidence() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let evidence = Bytes::from_slice(&env, b"informe final: robot seguidor de linea");
+    let hash: BytesN<32> = env.crypto().sha256(&evidence).into();
+
+    let badge_id = client.issue_badge(
+        &admin,
+        &Address::generate(&env),
+        &1_u64,
+        &String::from_str(&env, "Taller de Robotica"),
+        &String::from_str(&env, "https://universidad.edu/badges/robotica.png"),
+        &100_i128,
+        &hash,
+    );
+
+    assert!(client.verify_evidence(&badge_id, &evidence));
+    assert!(!client.verify_evidence(&badge_id, &Bytes::from_slice(&env, b"otro informe")));
+    assert!(!client.verify_evidence(&99_u64, &evidence));
+}
+
+#[test]
+fn test_verify_badge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+
+    let status = client.verify_badge(&badge_id, &student);
+    assert!(status.exists && status.owned_by_student && !status.revoked && !status.expired);
+    assert_eq!(status.owner, Some(student.clone()));
+    assert!(!client.verify_badge(&badge_id, &other).owned_by_student);
+
+    client.revoke_badge(&admin, &badge_id, &BytesN::from_array(&env, &[7u8; 32]), &false);
+    assert!(client.verify_badge(&badge_id, &student).revoked);
+
+    let missing = client.verify_badge(&99_u64, &student);
+    assert!(!missing.exists && !missing.owned_by_student);
+    assert_eq!(missing.owner, None);
+}
+
+fn batch_leaf(env: &Env, student: &Address, activity_id: u64) -> BatchLeaf {
+    BatchLeaf {
+        student: student.clone(),
+        activity_id,
+        description_hash: BytesN::from_array(env, &[activity_id as u8; 32]),
+    }
+}
+
+/// Árbol de cuatro hojas: retorna la raíz y la prueba de cada hoja.
+fn merkle_tree(env: &Env, leaves: &[BatchLeaf; 4]) -> (BytesN<32>, [Vec<BytesN<32>>; 4]) {
+    let h: [BytesN<32>; 4] =
+        core::array::from_fn(|i| AchievementBadges::leaf_hash(env, &leaves[i]));
+    let left = AchievementBadges::hash_pair(env, &h[0], &h[1]);
+    let right = AchievementBadges::hash_pair(env, &h[2], &h[3]);
+    let root = AchievementBadges::hash_pair(env, &left, &right);
+    let proofs = [
+        vec![env, h[1].clone(), right.clone()],
+        vec![env, h[0].clone(), right.clone()],
+        vec![env, h[3].clone(), left.clone()],
+        vec![env, h[2].clone(), left.clone()],
+    ];
+    (root, proofs)
+}
+
+#[test]
+fn test_claim_from_merkle_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let students: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    let leaves: [BatchLeaf; 4] = core::array::from_fn(|i| batch_leaf(&env, &students[i], 10));
+    let (root, proofs) = merkle_tree(&env, &leaves);
+
+    create_class(&env, &client, &admin, 7);
+    client.publish_batch(&admin, &1_u64, &root, &7_u64);
+
+    let badge_id = client.claim(&1_u64, &leaves[2], &proofs[2]);
+    let badge = client.get_badge(&badge_id);
+    assert_eq!(badge.student, students[2]);
+    assert_eq!(badge.class_id, Some(7));
+    assert_eq!(badge.activity_id, 10);
+    assert_eq!(badge.reviewer, admin);
+    assert_eq!(client.claimed_badge(&1_u64, &leaves[2]), Some(badge_id));
+    assert_eq!(client.claimed_badge(&1_u64, &leaves[0]), None);
+
+    client.claim(&1_u64, &leaves[0], &proofs[0]);
+    assert_eq!(client.class_badge_count(&7_u64), 2);
+}
+
+#[test]
+#[should_panic(expected = "leaf_already_claimed")]
+fn test_batch_leaf_claimed_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let students: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    let leaves: [BatchLeaf; 4] = core::array::from_fn(|i| batch_leaf(&env, &students[i], 10));
+    let (root, proofs) = merkle_tree(&env, &leaves);
+
+    create_class(&env, &client, &admin, 7);
+    client.publish_batch(&admin, &1_u64, &root, &7_u64);
+    client.claim(&1_u64, &leaves[1], &proofs[1]);
+    client.claim(&1_u64, &leaves[1], &proofs[1]);
+}
+
+#[test]
+#[should_panic(expected = "invalid_proof")]
+fn test_claim_rejects_leaf_outside_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let students: [Address; 4] = core::array::from_fn(|_| Address::generate(&env));
+    let leaves: [BatchLeaf; 4] = core::array::from_fn(|i| batch_leaf(&env, &students[i], 10));
+    let (root, proofs) = merkle_tree(&env, &leaves);
+
+    create_class(&env, &client, &admin, 7);
+    client.publish_batch(&admin, &1_u64, &root, &7_u64);
+    // Otra actividad para el mismo estudiante: la hoja no está en el árbol.
+    client.claim(&1_u64, &batch_leaf(&env, &students[0], 11), &proofs[0]);
+}
+
+#[test]
+fn test_badges_by_reviewer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    create_class(&env, &client, &admin, 7);
+    let first = issue(&env, &client, &reviewer, &Address::generate(&env));
+    issue(&env, &client, &admin, &Address::generate(&env));
+    let student = Address::generate(&env);
+    let third = client.issue_class_badge(&reviewer, &student, &7_u64, &1_u64, &hash);
+
+    assert_eq!(client.reviewer_badge_count(&reviewer), 2);
+    assert_eq!(client.badges_by_reviewer(&reviewer, &0, &10), vec![&env, first, third]);
+    assert_eq!(client.reviewer_badge_count(&admin), 1);
+}
+
+#[test]
+fn test_reviewer_scope() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    client.set_reviewer_scope(&admin, &reviewer, &vec![&env, 1_u64, 2_u64]);
+    assert_eq!(client.reviewer_scope(&reviewer), vec![&env, 1_u64, 2_u64]);
+
+    // `issue` usa activity_id 1, dentro del alcance.
+    issue(&env, &client, &reviewer, &Address::generate(&env));
+
+    client.set_reviewer_scope(&admin, &reviewer, &vec![&env]);
+    assert_eq!(client.reviewer_scope(&reviewer).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "activity_out_of_scope")]
+fn test_reviewer_outside_scope() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+    client.set_reviewer_scope(&admin, &reviewer, &vec![&env, 5_u64]);
+    issue(&env, &client, &reviewer, &Address::generate(&env));
+}
+
+fn create_opt_in_class(env: &Env, client: &AchievementBadgesClient, admin: &Address) -> u64 {
+    client.create_badge_class(
+        admin,
+        &9_u64,
+        &String::from_str(env, "Recuperacion de entrega tardia"),
+        &String::from_str(env, "ipfs://QmLate"),
+        &BytesN::from_array(env, &[4u8; 32]),
+        &10_i128,
+        &true,
+    );
+    9
+}
+
+#[test]
+fn test_accept_badge_offer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+
+    assert!(client.pending_offer(&badge_id).is_some());
+    assert_eq!(client.student_badge_count(&student), 0);
+    assert_eq!(client.class_badge_count(&class_id), 0);
+    assert!(!client.is_valid(&badge_id));
+    assert!(client.verify_badge(&badge_id, &student).pending);
+
+    client.accept_badge(&badge_id);
+
+    assert_eq!(client.pending_offer(&badge_id), None);
+    assert_eq!(client.get_student_badges(&student, &0, &10), vec![&env, badge_id]);
+    assert_eq!(client.badges_of_class(&class_id, &0, &10), vec![&env, badge_id]);
+    assert!(client.is_valid(&badge_id));
+}
+
+#[test]
+#[should_panic(expected = "badge_not_found")]
+fn test_decline_badge_offer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+
+    client.decline_badge(&badge_id);
+
+    assert_eq!(client.student_badge_count(&student), 0);
+    assert_eq!(client.pending_offer(&badge_id), None);
+    client.get_badge(&badge_id);
+}
+
+#[test]
+#[should_panic(expected = "offer_expired")]
+fn test_expired_offer_cannot_be_accepted() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id =
+        client.issue_class_badge(&admin, &Address::generate(&env), &class_id, &1_u64, &hash);
+
+    env.ledger().with_mut(|l| l.timestamp += OFFER_WINDOW + 1);
+    client.accept_badge(&badge_id);
+}
+
+#[test]
+fn test_expired_offer_allows_reissue() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let expired = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+
+    env.ledger().with_mut(|l| l.timestamp += OFFER_WINDOW + 1);
+    let badge_id = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+
+    assert_eq!(client.pending_offer(&expired), None);
+    assert!(client.try_get_badge(&expired).is_err());
+    assert_eq!(client.badge_for(&student, &1_u64), Some(badge_id));
+    client.accept_badge(&badge_id);
+    assert_eq!(client.get_student_badges(&student, &0, &10), vec![&env, badge_id]);
+}
+
+#[test]
+#[should_panic(expected = "duplicate_badge")]
+fn test_pending_offer_blocks_reissue() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+    client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+}
+
+#[test]
+#[should_panic(expected = "offer_not_found")]
+fn test_accept_badge_without_offer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    client.accept_badge(&badge_id);
+}
+
+#[test]
+#[should_panic(expected = "badge_revoked")]
+fn test_revoked_offer_cannot_be_accepted() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+    client.revoke_badge(&admin, &badge_id, &hash, &false);
+    client.accept_badge(&badge_id);
+}
+
+#[test]
+fn test_pending_offer_has_no_owner() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id = client.issue_class_badge(&admin, &student, &class_id, &1_u64, &hash);
+    assert!(client.try_owner_of(&badge_id).is_err());
+    assert!(client.try_token_uri(&badge_id).is_err());
+
+    client.accept_badge(&badge_id);
+    assert_eq!(client.owner_of(&badge_id), student);
+    assert_eq!(client.token_uri(&badge_id), String::from_str(&env, "ipfs://QmLate"));
+}
+
+fn issue_activity(
+    env: &Env,
+    client: &AchievementBadgesClient,
+    admin: &Address,
+    student: &Address,
+    activity_id: u64,
+) -> u64 {
+    client.issue_badge(
+        admin,
+        student,
+        &activity_id,
+        &String::from_str(env, "Taller"),
+        &String::from_str(env, "ipfs://QmTaller"),
+        &10_i128,
+        &BytesN::from_array(env, &[0u8; 32]),
+    )
+}
+
+#[test]
+fn test_path_with_threshold_issues_meta_badge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    create_class(&env, &client, &admin, 1); // Taller
+    create_class(&env, &client, &admin, 2); // Workshop Master
+    client.create_path(&admin, &5_u64, &2_u64, &vec![&env, Requirement::Class(1)], &3);
+
+    client.issue_class_badge(&admin, &student, &1_u64, &100_u64, &hash);
+    client.issue_class_badge(&admin, &student, &1_u64, &101_u64, &hash);
+    let progress = client.path_progress(&student, &5_u64);
+    assert_eq!((progress.matched_badges, progress.needed), (2, 3));
+    assert!(!progress.completed);
+
+    client.issue_class_badge(&admin, &student, &1_u64, &102_u64, &hash);
+
+    let progress = client.path_progress(&student, &5_u64);
+    assert!(progress.completed);
+    let meta_badge_id = progress.meta_badge_id.unwrap();
+    let meta = client.get_badge(&meta_badge_id);
+    assert_eq!(meta.class_id, Some(2));
+    assert_eq!(meta.student, student);
+    assert_eq!(client.student_badge_count(&student), 4);
+
+    // Más badges de la clase no vuelven a emitir el meta-badge.
+    client.issue_class_badge(&admin, &student, &1_u64, &103_u64, &hash);
+    assert_eq!(client.class_badge_count(&2_u64), 1);
+}
+
+#[test]
+fn test_path_requires_every_requirement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let requirements = vec![&env, Requirement::Activity(1), Requirement::Activity(2)];
+
+    create_class(&env, &client, &admin, 2);
+    client.create_path(&admin, &5_u64, &2_u64, &requirements, &0);
+    client.set_activity_repeatable(&admin, &1_u64, &true);
+
+    issue_activity(&env, &client, &admin, &student, 1);
+    issue_activity(&env, &client, &admin, &student, 1);
+    let progress = client.path_progress(&student, &5_u64);
+    assert_eq!(progress.satisfied, vec![&env, Requirement::Activity(1)]);
+    assert!(!progress.completed);
+
+    issue_activity(&env, &client, &admin, &student, 2);
+    assert!(client.path_progress(&student, &5_u64).completed);
+}
+
+#[test]
+fn test_gated_class_requires_completed_path() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    create_class(&env, &client, &admin, 2);
+    create_class(&env, &client, &admin, 3); // Avanzado
+    client.create_path(&admin, &5_u64, &2_u64, &vec![&env, Requirement::Activity(1)], &0);
+    client.set_class_prerequisite(&admin, &3_u64, &Some(5_u64));
+
+    assert!(client
+        .try_issue_class_badge(&admin, &student, &3_u64, &9_u64, &hash)
+        .is_err());
+
+    issue_activity(&env, &client, &admin, &student, 1);
+    let badge_id = client.issue_class_badge(&admin, &student, &3_u64, &9_u64, &hash);
+    assert_eq!(client.get_badge(&badge_id).class_id, Some(3));
+}
+
+#[test]
+#[should_panic(expected = "meta_class_has_prerequisite")]
+fn test_meta_class_cannot_be_gated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    create_class(&env, &client, &admin, 2);
+    create_class(&env, &client, &admin, 3);
+    client.create_path(&admin, &5_u64, &2_u64, &vec![&env, Requirement::Activity(1)], &0);
+    client.create_path(&admin, &6_u64, &3_u64, &vec![&env, Requirement::Activity(2)], &0);
+    client.set_class_prerequisite(&admin, &2_u64, &Some(6_u64));
+}
+
+#[test]
+#[should_panic(expected = "meta_class_has_prerequisite")]
+fn test_path_rejects_gated_meta_class() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    create_class(&env, &client, &admin, 2);
+    create_class(&env, &client, &admin, 3);
+    client.create_path(&admin, &5_u64, &2_u64, &vec![&env, Requirement::Activity(1)], &0);
+    client.set_class_prerequisite(&admin, &3_u64, &Some(5_u64));
+    client.create_path(&admin, &6_u64, &3_u64, &vec![&env, Requirement::Activity(2)], &0);
+}
+
+#[test]
+fn test_revocation_keeps_path_progress() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let reason = BytesN::from_array(&env, &[9u8; 32]);
+    let requirements = vec![&env, Requirement::Activity(1), Requirement::Activity(2)];
+
+    create_class(&env, &client, &admin, 2);
+    create_class(&env, &client, &admin, 3);
+    client.create_path(&admin, &5_u64, &2_u64, &requirements, &0);
+    client.create_path(&admin, &6_u64, &3_u64, &vec![&env, Requirement::Activity(1)], &2);
+
+    let first = issue_activity(&env, &client, &admin, &student, 1);
+    issue_activity(&env, &client, &admin, &student, 2);
+    client.revoke_badge(&admin, &first, &reason, &false);
+
+    // La ruta completa conserva su meta-badge y la incompleta su avance.
+    let progress = client.path_progress(&student, &5_u64);
+    assert!(progress.completed);
+    assert!(client.is_valid(&progress.meta_badge_id.unwrap()));
+    assert_eq!(client.path_progress(&student, &6_u64).matched_badges, 1);
+}
+
+#[test]
+#[should_panic(expected = "duplicate_badge")]
+fn test_duplicate_badge_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    issue(&env, &client, &admin, &student);
+    issue(&env, &client, &admin, &student);
+}
+
+#[test]
+#[should_panic(expected = "duplicate_badge")]
+fn test_duplicate_class_badge_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    create_class(&env, &client, &admin, 7);
+    issue(&env, &client, &admin, &student);
+    client.issue_class_badge(&admin, &student, &7_u64, &1_u64, &hash);
+}
+
+#[test]
+fn test_badge_for_and_activity_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let s1 = Address::generate(&env);
+    let s2 = Address::generate(&env);
+
+    let first = issue(&env, &client, &admin, &s1);
+    let second = issue(&env, &client, &admin, &s2);
+    issue_activity(&env, &client, &admin, &s1, 2);
+
+    assert_eq!(client.badge_for(&s1, &1_u64), Some(first));
+    assert_eq!(client.badge_for(&s2, &2_u64), None);
+    assert_eq!(client.activity_badge_count(&1_u64), 2);
+    assert_eq!(client.badges_of_activity(&1_u64, &0, &10), vec![&env, first, second]);
+}
+
+#[test]
+fn test_repeatable_activity_and_reissue_after_revocation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.set_activity_repeatable(&admin, &2_u64, &true);
+    assert!(client.activity_repeatable(&2_u64));
+    issue_activity(&env, &client, &admin, &student, 2);
+    let latest = issue_activity(&env, &client, &admin, &student, 2);
+    assert_eq!(client.badge_for(&student, &2_u64), Some(latest));
+
+    let revoked = issue(&env, &client, &admin, &student);
+    client.revoke_badge(&admin, &revoked, &BytesN::from_array(&env, &[7u8; 32]), &false);
+    let reissued = issue(&env, &client, &admin, &student);
+    assert_eq!(client.badge_for(&student, &1_u64), Some(reissued));
+}
+
+#[test]
+fn test_class_badges_expire() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+    let year = 365 * 24 * 60 * 60;
+
+    create_class(&env, &client, &admin, 7);
+    client.set_class_validity(&admin, &7_u64, &Some(year));
+    let badge_id = client.issue_class_badge(&admin, &student, &7_u64, &1_u64, &hash);
+    // Sin validez, los badges sueltos no vencen.
+    let forever = issue_activity(&env, &client, &admin, &student, 2);
+
+    assert_eq!(client.valid_until(&badge_id), Some(env.ledger().timestamp() + year));
+    assert!(client.is_valid(&badge_id));
+
+    env.ledger().with_mut(|l| l.timestamp += year + 1);
+
+    assert!(!client.is_valid(&badge_id));
+    assert!(client.verify_badge(&badge_id, &student).expired);
+    assert!(client.is_valid(&forever));
+    client.mark_expired(&badge_id);
+    assert!(client.try_mark_expired(&badge_id).is_err());
+}
+
+#[test]
+fn test_renew_badge_keeps_history() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let now = env.ledger().timestamp();
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    client.renew_badge(&admin, &badge_id, &(now + 100));
+    env.ledger().with_mut(|l| l.timestamp += 150);
+    assert!(!client.is_valid(&badge_id));
+
+    client.renew_badge(&admin, &badge_id, &(now + 1_000));
+
+    assert!(client.is_valid(&badge_id));
+    let history = client.renewal_history(&badge_id);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(0).unwrap().previous_valid_until, None);
+    assert_eq!(history.get(1).unwrap().previous_valid_until, Some(now + 100));
+    assert_eq!(history.get(1).unwrap().valid_until, now + 1_000);
+}
+
+#[test]
+#[should_panic(expected = "badge_not_expired")]
+fn test_mark_expired_before_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    client.mark_expired(&badge_id);
+}
+
+#[test]
+fn test_endorse_badge_and_class() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let employer = Address::generate(&env);
+    let university = Address::generate(&env);
+    let comment = BytesN::from_array(&env, &[5u8; 32]);
+
+    create_class(&env, &client, &admin, 7);
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+
+    let badge_target = EndorsementTarget::Badge(badge_id);
+    let first = client.endorse(&employer, &badge_target, &comment);
+    let second = client.endorse(&university, &badge_target, &comment);
+    client.endorse(&employer, &EndorsementTarget::Class(7), &comment);
+
+    assert_eq!(client.endorsement_count(&badge_target), 2);
+    let page = client.endorsements_of(&badge_target, &1, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().endorser, university);
+    assert_eq!(client.endorsement_count(&EndorsementTarget::Class(7)), 1);
+    assert_eq!(client.endorsements_by(&employer, &0, &10).len(), 2);
+
+    client.revoke_endorsement(&employer, &first);
+    assert!(client.get_endorsement(&first).revoked);
+    assert!(!client.get_endorsement(&second).revoked);
+    // Tras retirarlo, puede volver a endosar.
+    client.endorse(&employer, &badge_target, &comment);
+    assert_eq!(client.endorsement_count(&badge_target), 3);
+}
+
+#[test]
+#[should_panic(expected = "already_endorsed")]
+fn test_endorse_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let employer = Address::generate(&env);
+    let comment = BytesN::from_array(&env, &[5u8; 32]);
+
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    client.endorse(&employer, &EndorsementTarget::Badge(badge_id), &comment);
+    client.endorse(&employer, &EndorsementTarget::Badge(badge_id), &comment);
+}
+
+#[test]
+#[should_panic(expected = "not_endorser")]
+fn test_only_endorser_can_revoke_endorsement() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let employer = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &Address::generate(&env));
+    let endorsement_id = client.endorse(
+        &employer,
+        &EndorsementTarget::Badge(badge_id),
+        &BytesN::from_array(&env, &[5u8; 32]),
+    );
+    client.revoke_endorsement(&admin, &endorsement_id);
+}
+
+#[test]
+fn test_showcase_orders_featured_and_skips_hidden() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let ids: std::vec::Vec<u64> = (1..=4)
+        .map(|activity_id| issue_activity(&env, &client, &admin, &student, activity_id))
+        .collect();
+    assert_eq!(client.showcase(&student), vec![&env, ids[0], ids[1], ids[2], ids[3]]);
+
+    client.set_featured(&student, &vec![&env, ids[2], ids[0]]);
+    client.set_badge_hidden(&student, &ids[1], &true);
+    assert_eq!(client.showcase(&student), vec![&env, ids[2], ids[0], ids[3]]);
+    assert!(client.is_hidden(&ids[1]));
+
+    // Ocultar un destacado lo quita de los destacados.
+    client.set_badge_hidden(&student, &ids[2], &true);
+    assert_eq!(client.featured_badges(&student), vec![&env, ids[0]]);
+    assert_eq!(client.showcase(&student), vec![&env, ids[0], ids[3]]);
+    assert_eq!(client.student_badge_count(&student), 4);
+}
+
+#[test]
+fn test_showcase_skips_revoked() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let ids: std::vec::Vec<u64> = (1..=3)
+        .map(|activity_id| issue_activity(&env, &client, &admin, &student, activity_id))
+        .collect();
+    client.set_featured(&student, &vec![&env, ids[2], ids[0]]);
+
+    client.revoke_badge(&admin, &ids[2], &hash, &false);
+    client.revoke_badge(&admin, &ids[1], &hash, &false);
+    assert_eq!(client.showcase(&student), vec![&env, ids[0]]);
+    assert!(client.try_set_featured(&student, &vec![&env, ids[1]]).is_err());
+}
+
+#[test]
+fn test_showcase_follows_migration() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+
+    let first = issue_activity(&env, &client, &admin, &old, 1);
+    let second = issue_activity(&env, &client, &admin, &old, 2);
+    client.set_featured(&old, &vec![&env, second]);
+
+    client.migrate_student(&admin, &old, &new);
+    assert_eq!(client.featured_badges(&old).len(), 0);
+    assert_eq!(client.showcase(&new), vec![&env, second, first]);
+}
+
+#[test]
+fn test_path_progress_follows_migration() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let requirements = vec![&env, Requirement::Activity(1), Requirement::Activity(2)];
+
+    create_class(&env, &client, &admin, 2);
+    client.create_path(&admin, &5_u64, &2_u64, &requirements, &0);
+    issue_activity(&env, &client, &admin, &old, 1);
+
+    client.migrate_student(&admin, &old, &new);
+    assert_eq!(client.path_progress(&old, &5_u64).matched_badges, 0);
+    assert_eq!(client.path_progress(&new, &5_u64).matched_badges, 1);
+
+    issue_activity(&env, &client, &admin, &new, 2);
+    let progress = client.path_progress(&new, &5_u64);
+    assert!(progress.completed);
+    assert_eq!(client.get_badge(&progress.meta_badge_id.unwrap()).student, new);
+}
+
+#[test]
+fn test_migration_merges_path_progress() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let requirements = vec![
+        &env,
+        Requirement::Activity(1),
+        Requirement::Activity(2),
+        Requirement::Activity(3),
+    ];
+
+    create_class(&env, &client, &admin, 2);
+    client.create_path(&admin, &5_u64, &2_u64, &requirements, &0);
+    issue_activity(&env, &client, &admin, &old, 1);
+    issue_activity(&env, &client, &admin, &new, 2);
+    issue_activity(&env, &client, &admin, &new, 3);
+    assert!(!client.path_progress(&new, &5_u64).completed);
+
+    client.migrate_student(&admin, &old, &new);
+    let progress = client.path_progress(&new, &5_u64);
+    assert_eq!(progress.matched_badges, 3);
+    assert_eq!(progress.satisfied.len(), 3);
+    assert!(progress.completed);
+    assert_eq!(client.get_badge(&progress.meta_badge_id.unwrap()).student, new);
+    assert_eq!(client.path_progress(&old, &5_u64).matched_badges, 0);
+}
+
+#[test]
+fn test_migration_keeps_existing_badge_for() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+
+    let old_badge = issue_activity(&env, &client, &admin, &old, 1);
+    let new_badge = issue_activity(&env, &client, &admin, &new, 1);
+
+    client.migrate_student(&admin, &old, &new);
+    assert_eq!(client.badge_for(&new, &1_u64), Some(new_badge));
+    assert_eq!(client.badge_for(&old, &1_u64), None);
+    assert_eq!(client.get_badge(&old_badge).student, new);
+    assert_eq!(client.student_badge_count(&new), 2);
+}
+
+#[test]
+fn test_pending_offer_follows_migration() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let hash = BytesN::from_array(&env, &[0u8; 32]);
+
+    let class_id = create_opt_in_class(&env, &client, &admin);
+    let badge_id = client.issue_class_badge(&admin, &old, &class_id, &1_u64, &hash);
+
+    assert_eq!(client.migrate_student(&admin, &old, &new), 0);
+    assert_eq!(client.get_badge(&badge_id).student, new);
+    assert_eq!(client.badge_for(&new, &1_u64), Some(badge_id));
+    assert_eq!(client.badge_for(&old, &1_u64), None);
+
+    client.accept_badge(&badge_id);
+    assert_eq!(env.auths()[0].0, new);
+    assert_eq!(client.get_student_badges(&new, &0, &10), vec![&env, badge_id]);
+}
+
+#[test]
+#[should_panic(expected = "not_badge_owner")]
+fn test_cannot_feature_others_badge() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &other);
+    client.set_featured(&student, &vec![&env, badge_id]);
+}
+
+#[test]
+#[should_panic(expected = "duplicate_featured")]
+fn test_cannot_feature_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    client.set_featured(&student, &vec![&env, badge_id, badge_id]);
+}
+
+#[test]
+fn test_admin_redacts_badge_title() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    let title = client.get_badge(&badge_id).activity_title;
+    client.redact(&admin, &badge_id);
+
+    let badge = client.get_badge(&badge_id);
+    assert_eq!(badge.activity_title, String::from_str(&env, "[redacted]"));
+    assert_eq!(badge.student, student);
+    assert_eq!(badge.points_awarded, 100);
+    assert_eq!(
+        client.redacted_hash(&badge_id),
+        Some(env.crypto().sha256(&title.to_bytes()).to_bytes())
+    );
+    assert!(client.is_valid(&badge_id));
+}
+
+#[test]
+#[should_panic(expected = "already_redacted")]
+fn test_redact_badge_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    client.redact(&student, &badge_id);
+    client.redact(&admin, &badge_id);
+}
+
+#[test]
+fn test_verify_content_after_redaction() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    let badge = client.get_badge(&badge_id);
+    let content = BadgeContent {
+        activity_title: badge.activity_title,
+        image_uri: badge.image_uri,
+    };
+    client.redact(&admin, &badge_id);
+
+    assert!(client.verify_content(&badge_id, &content));
+    let marker = BadgeContent {
+        activity_title: String::from_str(&env, "[redacted]"),
+        ..content
+    };
+    assert!(!client.verify_content(&badge_id, &marker));
+}
+
+#[test]
+fn test_compact_mode_keeps_only_content_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(
+        AchievementBadges,
+        (
+            &admin,
+            String::from_str(&env, "XPUni Badges"),
+            String::from_str(&env, "XPB"),
+            None::<Address>,
+            StorageMode::Compact,
+        ),
+    );
+    let client = AchievementBadgesClient::new(&env, &contract_id);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    assert_eq!(client.storage_mode(), StorageMode::Compact);
+
+    let badge = client.get_badge(&badge_id);
+    assert_eq!(badge.activity_title, String::from_str(&env, ""));
+    assert_eq!(badge.image_uri, String::from_str(&env, ""));
+    assert_eq!(badge.student, student);
+    assert_eq!(badge.points_awarded, 100);
+    // Sin la imagen on-chain no hay URI que retornar.
+    assert_eq!(client.token_uri(&badge_id), String::from_str(&env, ""));
+
+    let content = BadgeContent {
+        activity_title: String::from_str(&env, "Taller de Robotica"),
+        image_uri: String::from_str(&env, "https://universidad.edu/badges/robotica.png"),
+    };
+    assert!(client.verify_content(&badge_id, &content));
+    let tampered = BadgeContent {
+        activity_title: String::from_str(&env, "Taller de Robótica avanzada"),
+        ..content.clone()
+    };
+    assert!(!client.verify_content(&badge_id, &tampered));
+    let other_image = BadgeContent {
+        image_uri: String::from_str(&env, "ipfs://QmOtra"),
+        ..content
+    };
+    assert!(!client.verify_content(&badge_id, &other_image));
+}
+
+#[test]
+fn test_verify_content_in_full_mode() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    let badge_id = issue(&env, &client, &admin, &student);
+    let badge = client.get_badge(&badge_id);
+    let content = BadgeContent {
+        activity_title: badge.activity_title,
+        image_uri: badge.image_uri,
+    };
+    assert!(client.verify_content(&badge_id, &content));
+    assert!(!client.verify_content(&(badge_id + 1), &content));
+}
+
+#[test]
+fn test_team_badge_splits_and_mints_points() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let points_id = env.register(
+        school_points::SchoolPoints,
+        (
+            &admin,
+            String::from_str(&env, "XP Points"),
+            String::from_str(&env, "XP"),
+        ),
+    );
+    let points = school_points::SchoolPointsClient::new(&env, &points_id);
+    let badges_id = env.register(
+        AchievementBadges,
+        (
+            &admin,
+            String::from_str(&env, "XPUni Badges"),
+            String::from_str(&env, "XPB"),
+            Some(points_id.clone()),
+            StorageMode::Full,
+        ),
+    );
+    let client = AchievementBadgesClient::new(&env, &badges_id);
+    points.grant_role(&admin, &Role::Minter, &badges_id);
+
+    let team = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    // 101 × (1, 2, 1) / 4 = (25.25, 50.5, 25.25): el punto de resto va al segundo.
+    let team_id = client.issue_team_badge(
+        &admin,
+        &team,
+        &vec![&env, 1_u32, 2_u32, 1_u32],
+        &101_i128,
+        &5_u64,
+        &String::from_str(&env, "Hackathon 2025"),
+        &String::from_str(&env, "ipfs://QmHackathon"),
+        &BytesN::from_array(&env, &[0u8; 32]),
+        &symbol_short!("academic"),
+    );
+
+    let award = client.get_team(&team_id);
+    assert_eq!(award.members, team);
+    assert_eq!(award.badge_ids.len(), 3);
+    let expected = [25_i128, 51, 25];
+    for (i, member) in team.iter().enumerate() {
+        let badge_id = award.badge_ids.get_unchecked(i as u32);
+        assert_eq!(client.get_badge(&badge_id).student, member);
+        assert_eq!(client.get_badge(&badge_id).points_awarded, expected[i]);
+        assert_eq!(client.team_of(&badge_id), Some(team_id));
+        assert_eq!(points.balance(&member), expected[i]);
+    }
+}
+
+/// Eventos de la última invocación cuyo primer topic es `name`.
+fn count_events(env: &Env, name: &str) -> usize {
+    let topic = xdr::ScVal::Symbol(xdr::ScSymbol(name.try_into().unwrap()));
+    env.events()
+        .all()
+        .events()
+        .iter()
+        .filter(|event| match &event.body {
+            xdr::ContractEventBody::V0(body) => body.topics.first() == Some(&topic),
+        })
+        .count()
+}
+
+#[test]
+fn test_compact_team_badge_publishes_title_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(
+        AchievementBadges,
+        (
+            &admin,
+            String::from_str(&env, "XPUni Badges"),
+            String::from_str(&env, "XPB"),
+            None::<Address>,
+            StorageMode::Compact,
+        ),
+    );
+    let client = AchievementBadgesClient::new(&env, &contract_id);
+    let team = vec![&env, Address::generate(&env), Address::generate(&env)];
+
+    client.issue_team_badge(
+        &admin,
+        &team,
+        &vec![&env, 1_u32, 1_u32],
+        &10_i128,
+        &5_u64,
+        &String::from_str(&env, "Hackathon 2025"),
+        &String::from_str(&env, "ipfs://QmHackathon"),
+        &BytesN::from_array(&env, &[0u8; 32]),
+        &symbol_short!("academic"),
+    );
+
+    assert_eq!(count_events(&env, "team_cnt"), 1);
+    assert_eq!(count_events(&env, "content"), 0);
+}
+
+#[test]
+fn test_team_badge_remainder_goes_to_largest_fractions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let team = vec![
+        &env,
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+
+    // 100 / 3 = 33 a cada uno con igual fracción: el resto va al primero.
+    let equal = client.issue_team_badge(
+        &admin,
+        &team,
+        &vec![&env, 1_u32, 1_u32, 1_u32],
+        &100_i128,
+        &5_u64,
+        &String::from_str(&env, "Hackathon 2025"),
+        &String::from_str(&env, "ipfs://QmHackathon"),
+        &BytesN::from_array(&env, &[0u8; 32]),
+        &symbol_short!("academic"),
+    );
+    // 10 × (1, 2) / 3 = (3.33, 6.66): el resto va al segundo.
+    let weighted = client.issue_team_badge(
+        &admin,
+        &vec![&env, team.get_unchecked(0), team.get_unchecked(1)],
+        &vec![&env, 1_u32, 2_u32],
+        &10_i128,
+        &6_u64,
+        &String::from_str(&env, "Proyecto final"),
+        &String::from_str(&env, "ipfs://QmProyecto"),
+        &BytesN::from_array(&env, &[0u8; 32]),
+        &symbol_short!("academic"),
+    );
+
+    let points_of = |team_id: u64| -> std::vec::Vec<i128> {
+        client
+            .get_team(&team_id)
+            .badge_ids
+            .iter()
+            .map(|badge_id| client.get_badge(&badge_id).points_awarded)
+            .collect()
+    };
+    assert_eq!(points_of(equal), [34, 33, 33]);
+    assert_eq!(points_of(weighted), [3, 7]);
+}
+
+#[test]
+#[should_panic(expected = "shares_length_mismatch")]
+fn test_team_badge_requires_one_share_per_member() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    client.issue_team_badge(
+        &admin,
+        &vec![&env, Address::generate(&env), Address::generate(&env)],
+        &vec![&env, 1_u32],
+        &100_i128,
+        &5_u64,
+        &String::from_str(&env, "Hackathon 2025"),
+        &String::from_str(&env, "ipfs://QmHackathon"),
+        &BytesN::from_array(&env, &[0u8; 32]),
+        &symbol_short!("academic"),
+    );
+}
+
+#[test]
+fn test_query_badges_combines_filters() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let reviewer = Address::generate(&env);
+    client.grant_role(&admin, &Role::Reviewer, &reviewer);
+
+    const DAY: u64 = 86_400;
+    let start = 1_735_689_600; // 2025-01-01T00:00:00Z
+    let issue_at = |timestamp: u64, reviewer: &Address, activity_id: u64| {
+        env.ledger().with_mut(|l| l.timestamp = timestamp);
+        client.issue_badge(
+            reviewer,
+            &Address::generate(&env),
+            &activity_id,
+            &String::from_str(&env, "Taller"),
+            &String::from_str(&env, "ipfs://QmTaller"),
+            &10_i128,
+            &BytesN::from_array(&env, &[0u8; 32]),
+        )
+    };
+    let b0 = issue_at(start, &admin, 42);
+    let b1 = issue_at(start + DAY + 60, &reviewer, 42);
+    let b2 = issue_at(start + DAY + 120, &admin, 7);
+    let b3 = issue_at(start + 40 * DAY, &admin, 42);
+
+    let query = |activity_id: Option<u64>, reviewer: Option<Address>, from: u64, to: u64| {
+        client
+            .query_badges(&BadgeQuery { activity_id, reviewer, from, to }, &None, &100)
+            .0
+    };
+    let semester = start + 30 * DAY;
+    assert_eq!(query(Some(42), None, start, semester), vec![&env, b0, b1]);
+    assert_eq!(query(Some(42), Some(reviewer.clone()), start, semester), vec![&env, b1]);
+    assert_eq!(query(None, Some(admin.clone()), 0, u64::MAX), vec![&env, b0, b2, b3]);
+    assert_eq!(query(None, None, start, start + 2 * DAY), vec![&env, b0, b1, b2]);
+    assert_eq!(query(None, None, start + 60, start + 2 * DAY), vec![&env, b1, b2]);
+    assert_eq!(client.badges_on_day(&(start / DAY + 1), &0, &10), vec![&env, b1, b2]);
+}
+
+#[test]
+fn test_query_badges_paginates_over_days() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let start = 1_735_689_600; // 2025-01-01T00:00:00Z
+    let mut expected = vec![&env];
+    for day in [0, 0, 3, 9] {
+        env.ledger().with_mut(|l| l.timestamp = start + day * 86_400);
+        expected.push_back(issue(&env, &client, &admin, &Address::generate(&env)));
+    }
+
+    let query = BadgeQuery {
+        activity_id: None,
+        reviewer: None,
+        from: start,
+        to: start + 10 * 86_400,
+    };
+    let (mut found, mut cursor, mut pages) = (vec![&env], None, 0);
+    loop {
+        let (badge_ids, next) = client.query_badges(&query, &cursor, &2);
+        found.append(&badge_ids);
+        pages += 1;
+        cursor = next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(found, expected);
+    assert!(pages > 2);
+}
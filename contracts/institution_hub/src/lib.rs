@@ -2,6 +2,8 @@
 use soroban_sdk::{
     contract, contractimpl, contracttype, symbol_short, Address, Env, Map, Vec,
 };
+use xpuni_access::{AccessControl, Pausable, Role};
+use xpuni_timelock::{QueuedOperation, TimelockAction, TimelockHook, Timelocked};
 
 // ── Storage keys ──────────────────────────────────────────────────────────────
 
@@ -13,8 +15,10 @@ pub enum DataKey {
     Institution(u64),
     /// Maps institution_id (u64) → institution_admin (Address)
     InstAdmin(u64),
-    /// List of registered institution ids
-    InstIds,
+    /// Number of registered institutions
+    InstCount,
+    /// Maps registration position (u32) → institution_id (u64)
+    InstAt(u32),
     /// Exchange rate: (from_contract_str, to_contract_str) encoded as a pair key
     Rate(Address, Address),
 }
@@ -25,65 +29,57 @@ pub enum DataKey {
 pub struct InstitutionHub;
 
 #[contractimpl]
+#[allow(deprecated)]
 impl InstitutionHub {
-    /// Initializes the hub with a platform admin. Can only be called once.
-    pub fn initialize(env: Env, admin: Address) {
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already_initialized");
-        }
+    /// Constructor: sets the platform admin atomically with the deploy.
+    pub fn __constructor(env: Env, admin: Address) {
         env.storage().instance().set(&DataKey::Admin, &admin);
-        let ids: Vec<u64> = Vec::new(&env);
-        env.storage().instance().set(&DataKey::InstIds, &ids);
+        xpuni_access::init(&env, &admin);
     }
 
-    // ── Admin helpers ─────────────────────────────────────────────────────────
-
-    fn require_admin(env: &Env) -> Address {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-        admin
-    }
+    // ── Admin ─────────────────────────────────────────────────────────────────
 
     pub fn admin(env: Env) -> Address {
         env.storage().instance().get(&DataKey::Admin).unwrap()
     }
 
-    pub fn set_admin(env: Env, new_admin: Address) {
-        Self::require_admin(&env);
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
-    }
-
     // ── Institution registry ───────────────────────────────────────────────────
 
-    /// Registers an institution. Caller must be the platform admin.
+    /// Registers an institution. Caller must hold the `Admin` role.
+    /// The registry lives in persistent storage, one entry per institution, so the
+    /// contract instance does not grow with the number of institutions.
     /// `institution_id`: off-chain DB id, `token_contract`: SEP-41 contract address,
     /// `inst_admin`: the keypair authorized to mint/clawback on that token contract.
     pub fn register_institution(
         env: Env,
+        caller: Address,
         institution_id: u64,
         token_contract: Address,
         inst_admin: Address,
     ) {
-        Self::require_admin(&env);
+        xpuni_access::require_role(&env, &caller, Role::Admin);
 
-        if env.storage().instance().has(&DataKey::Institution(institution_id)) {
+        if env.storage().persistent().has(&DataKey::Institution(institution_id)) {
             panic!("institution_already_registered");
         }
 
+        let inst_key = DataKey::Institution(institution_id);
+        env.storage().persistent().set(&inst_key, &token_contract);
+        xpuni_common::extend_persistent(&env, &inst_key);
+
+        let admin_key = DataKey::InstAdmin(institution_id);
+        env.storage().persistent().set(&admin_key, &inst_admin);
+        xpuni_common::extend_persistent(&env, &admin_key);
+
+        let count = Self::institution_count(env.clone());
         env.storage()
-            .instance()
-            .set(&DataKey::Institution(institution_id), &token_contract);
+            .persistent()
+            .set(&DataKey::InstAt(count), &institution_id);
+        xpuni_common::extend_persistent(&env, &DataKey::InstAt(count));
         env.storage()
-            .instance()
-            .set(&DataKey::InstAdmin(institution_id), &inst_admin);
-
-        let mut ids: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::InstIds)
-            .unwrap_or_else(|| Vec::new(&env));
-        ids.push_back(institution_id);
-        env.storage().instance().set(&DataKey::InstIds, &ids);
+            .persistent()
+            .set(&DataKey::InstCount, &(count + 1));
+        xpuni_common::extend_persistent(&env, &DataKey::InstCount);
 
         env.events().publish(
             (symbol_short!("reg_inst"), institution_id),
@@ -93,31 +89,39 @@ impl InstitutionHub {
 
     pub fn get_institution(env: Env, institution_id: u64) -> Address {
         env.storage()
-            .instance()
+            .persistent()
             .get(&DataKey::Institution(institution_id))
             .unwrap_or_else(|| panic!("institution_not_found"))
     }
 
     pub fn get_inst_admin(env: Env, institution_id: u64) -> Address {
         env.storage()
-            .instance()
+            .persistent()
             .get(&DataKey::InstAdmin(institution_id))
             .unwrap_or_else(|| panic!("institution_not_found"))
     }
 
-    /// Returns all registered (institution_id, token_contract) pairs as a Map.
-    pub fn get_institutions(env: Env) -> Map<u64, Address> {
-        let ids: Vec<u64> = env
-            .storage()
-            .instance()
-            .get(&DataKey::InstIds)
-            .unwrap_or_else(|| Vec::new(&env));
+    /// Returns the number of registered institutions.
+    pub fn institution_count(env: Env) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::InstCount)
+            .unwrap_or(0)
+    }
+
+    /// Returns up to `limit` (institution_id, token_contract) pairs as a Map, starting
+    /// at registration position `start`.
+    pub fn get_institutions(env: Env, start: u32, limit: u32) -> Map<u64, Address> {
+        let end = start
+            .saturating_add(limit.min(xpuni_common::MAX_PAGE_SIZE))
+            .min(Self::institution_count(env.clone()));
 
         let mut result: Map<u64, Address> = Map::new(&env);
-        for id in ids.iter() {
+        for n in start..end {
+            let id: u64 = env.storage().persistent().get(&DataKey::InstAt(n)).unwrap();
             if let Some(contract) = env
                 .storage()
-                .instance()
+                .persistent()
                 .get::<DataKey, Address>(&DataKey::Institution(id))
             {
                 result.set(id, contract);
@@ -128,19 +132,11 @@ impl InstitutionHub {
 
     // ── Exchange rates ────────────────────────────────────────────────────────
 
-    /// Sets the exchange rate from `from_contract` to `to_contract`.
+    /// Sets the exchange rate from `from_contract` to `to_contract`. Only reachable
+    /// through a queued `SetExchangeRate` action.
     /// `rate` is scaled by 1_000_000: a rate of 1_000_000 means 1:1.
     /// E.g., rate = 500_000 means 1 FROM = 0.5 TO.
-    pub fn set_exchange_rate(
-        env: Env,
-        from_contract: Address,
-        to_contract: Address,
-        rate: i128,
-    ) {
-        Self::require_admin(&env);
-        if rate <= 0 {
-            panic!("rate_must_be_positive");
-        }
+    fn set_exchange_rate(env: &Env, from_contract: Address, to_contract: Address, rate: i128) {
         env.storage()
             .instance()
             .set(&DataKey::Rate(from_contract.clone(), to_contract.clone()), &rate);
@@ -160,24 +156,26 @@ impl InstitutionHub {
 
     // ── Swap recording ────────────────────────────────────────────────────────
 
-    /// Records a swap event on-chain. The platform admin calls this AFTER successfully
+    /// Records a swap event on-chain. A `Minter` calls this AFTER successfully
     /// performing the clawback on `from_contract` and mint on `to_contract` off-chain.
     /// Returns amount_out so callers can verify the calculation.
     ///
     /// This function enforces:
-    ///   - Caller is platform admin
+    ///   - Caller holds the `Minter` role and the hub is not paused
     ///   - Both contracts are registered
     ///   - A rate exists between them
     ///   - amount_out matches the rate calculation
     pub fn record_swap(
         env: Env,
+        caller: Address,
         student: Address,
         from_contract: Address,
         to_contract: Address,
         amount_in: i128,
         amount_out: i128,
     ) -> i128 {
-        Self::require_admin(&env);
+        xpuni_access::require_not_paused(&env);
+        xpuni_access::require_role(&env, &caller, Role::Minter);
 
         if amount_in <= 0 {
             panic!("amount_in_must_be_positive");
@@ -217,3 +215,32 @@ impl InstitutionHub {
         amount_in * rate / 1_000_000_i128
     }
 }
+
+#[contractimpl(contracttrait)]
+impl AccessControl for InstitutionHub {}
+
+#[contractimpl(contracttrait)]
+impl Pausable for InstitutionHub {}
+
+impl TimelockHook for InstitutionHub {
+    const EXCHANGE_RATES: bool = true;
+
+    fn apply_action(env: &Env, action: TimelockAction) {
+        match action {
+            TimelockAction::SetAdmin(new_admin) => {
+                let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+                xpuni_access::transfer_admin(env, &admin, &new_admin);
+                env.storage().instance().set(&DataKey::Admin, &new_admin);
+            }
+            TimelockAction::SetExchangeRate(from_contract, to_contract, rate) => {
+                Self::set_exchange_rate(env, from_contract, to_contract, rate);
+            }
+            _ => panic!("unsupported_action"),
+        }
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl Timelocked for InstitutionHub {}
+
+mod test;
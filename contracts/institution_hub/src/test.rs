@@ -0,0 +1,154 @@
+#![cfg(test)]
+use super::*;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Env,
+};
+
+fn setup(env: &Env) -> (InstitutionHubClient<'_>, Address) {
+    let admin = Address::generate(env);
+    let contract_id = env.register(InstitutionHub, (&admin,));
+    let client = InstitutionHubClient::new(env, &contract_id);
+    (client, admin)
+}
+
+#[test]
+fn test_register_institution() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let token = Address::generate(&env);
+    let inst_admin = Address::generate(&env);
+
+    client.register_institution(&admin, &1_u64, &token, &inst_admin);
+
+    assert_eq!(client.get_institution(&1_u64), token);
+    assert_eq!(client.get_inst_admin(&1_u64), inst_admin);
+    assert_eq!(client.get_institutions(&0, &10).len(), 1);
+}
+
+#[test]
+fn test_get_institutions_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    for id in 10..15_u64 {
+        client.register_institution(&admin, &id, &Address::generate(&env), &admin);
+    }
+
+    assert_eq!(client.institution_count(), 5);
+    let page = client.get_institutions(&1, &2);
+    assert_eq!(page.len(), 2);
+    assert_eq!(page.keys(), vec![&env, 11_u64, 12_u64]);
+    assert_eq!(client.get_institutions(&4, &10).len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "institution_already_registered")]
+fn test_register_institution_twice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let token = Address::generate(&env);
+
+    client.register_institution(&admin, &1_u64, &token, &admin);
+    client.register_institution(&admin, &1_u64, &token, &admin);
+}
+
+#[test]
+fn test_exchange_rate_through_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(
+        &admin,
+        &TimelockAction::SetExchangeRate(from.clone(), to.clone(), 500_000),
+        &delay,
+    );
+    assert_eq!(client.get_rate(&from, &to), 0);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+
+    assert_eq!(client.get_rate(&from, &to), 500_000);
+    assert_eq!(client.calculate_swap(&from, &to, &100_i128), 50);
+    let student = Address::generate(&env);
+    assert_eq!(client.record_swap(&admin, &student, &from, &to, &100_i128, &50_i128), 50);
+}
+
+#[test]
+#[should_panic(expected = "operation_not_ready")]
+fn test_exchange_rate_before_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let from = Address::generate(&env);
+    let to = Address::generate(&env);
+
+    let op_id = client.queue_action(
+        &admin,
+        &TimelockAction::SetExchangeRate(from, to, 500_000),
+        &client.min_delay(),
+    );
+    client.execute_action(&op_id);
+}
+
+#[test]
+#[should_panic(expected = "operation_not_found")]
+fn test_cancelled_action_cannot_execute() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin), &delay);
+    client.cancel_action(&admin, &op_id);
+    assert_eq!(client.pending_actions().len(), 0);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+}
+
+#[test]
+#[should_panic(expected = "operation_expired")]
+fn test_expired_action_cannot_execute() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin), &delay);
+
+    let window = xpuni_timelock::EXECUTION_WINDOW;
+    env.ledger().with_mut(|l| l.sequence_number += delay + window + 1);
+    client.execute_action(&op_id);
+}
+
+#[test]
+fn test_pending_actions_drops_expired() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let delay = client.min_delay();
+    let window = xpuni_timelock::EXECUTION_WINDOW;
+
+    let stale = TimelockAction::SetAdmin(Address::generate(&env));
+    client.queue_action(&admin, &stale, &delay);
+    env.ledger().with_mut(|l| l.sequence_number += delay + window);
+    let fresh = TimelockAction::SetAdmin(Address::generate(&env));
+    let fresh_id = client.queue_action(&admin, &fresh, &delay);
+    assert_eq!(client.pending_actions().len(), 2);
+
+    // La primera sale de la cola aunque su entrada ya esté archivada.
+    env.ledger().with_mut(|l| l.sequence_number += 1);
+    let pending = client.pending_actions();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap().op_id, fresh_id);
+}
@@ -1,5 +1,10 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, String, Vec};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, BytesN, Env, String, Vec,
+};
+use xpuni_access::{AccessControl, Pausable, Role};
+use xpuni_common::{Registry, StorageMode};
+use xpuni_timelock::{QueuedOperation, TimelockAction, TimelockHook, Timelocked};
 
 // ── Data structures ───────────────────────────────────────────────────────────
 
@@ -20,84 +25,86 @@ pub struct RedemptionRecord {
     pub redeemed_at: u64,
 }
 
+/// Canje guardado en modo `Compact`: en vez de `reward_name` guarda su hash.
+#[contracttype]
+#[derive(Clone)]
+struct CompactRecord {
+    student: Address,
+    points_spent: i128,
+    redeemed_at: u64,
+    content_hash: BytesN<32>,
+}
+
+/// Forma en que se guarda cada canje en el registro.
+#[contracttype]
+#[derive(Clone)]
+enum StoredRecord {
+    Full(RedemptionRecord),
+    Compact(CompactRecord),
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub enum DataKey {
-    Admin,
-    RecordCount,
-    Record(u64),
-    StudentRecords(Address),
+    /// record_id → SHA-256 del `reward_name` borrado
+    Redacted(u64),
 }
 
+/// Registro de canjes: `record_id → StoredRecord`, con índice paginado por estudiante.
+const RECORDS: Registry = Registry::new(symbol_short!("record"));
+
 // ── Contract ──────────────────────────────────────────────────────────────────
 
 #[contract]
 pub struct RedemptionRecords;
 
 #[contractimpl]
+#[allow(deprecated)]
 impl RedemptionRecords {
-    /// Inicializa el contrato con el admin. Solo puede llamarse una vez.
-    pub fn initialize(env: Env, admin: Address) {
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already_initialized");
-        }
-        env.storage().instance().set(&DataKey::Admin, &admin);
-        env.storage().instance().set(&DataKey::RecordCount, &0_u64);
+    /// Constructor: fija el admin y el modo de almacenamiento de forma atómica con el
+    /// deploy.
+    pub fn __constructor(env: Env, admin: Address, storage_mode: StorageMode) {
+        xpuni_common::set_admin(&env, &admin);
+        xpuni_common::set_storage_mode(&env, storage_mode);
+        xpuni_access::init(&env, &admin);
     }
 
-    /// Registra un canje on-chain. Solo el admin puede llamar esto.
-    /// Retorna el record_id asignado.
+    /// Registra un canje on-chain. Requiere el rol `Partner`. En modo `Compact`,
+    /// `reward_name` no se guarda: se publica en el evento `content` y `get_record` lo
+    /// retorna vacío. Retorna el record_id asignado.
     pub fn record_redemption(
         env: Env,
+        partner: Address,
         student: Address,
         reward_name: String,
         points_spent: i128,
     ) -> u64 {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-
-        let record_id: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::RecordCount)
-            .unwrap_or(0);
-
-        let record = RedemptionRecord {
-            record_id,
-            student: student.clone(),
-            reward_name,
-            points_spent,
-            redeemed_at: env.ledger().timestamp(),
-        };
+        xpuni_access::require_not_paused(&env);
+        xpuni_access::require_role(&env, &partner, Role::Partner);
 
-        // Persistir registro con TTL extendido (~10M ledgers ≈ varios años)
-        env.storage()
-            .persistent()
-            .set(&DataKey::Record(record_id), &record);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Record(record_id), 10_000_000, 10_000_000);
-
-        // Actualizar lista de canjes del estudiante
-        let mut student_records: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&DataKey::StudentRecords(student.clone()))
-            .unwrap_or_else(|| Vec::new(&env));
-
-        student_records.push_back(record_id);
-
-        env.storage()
-            .persistent()
-            .set(&DataKey::StudentRecords(student.clone()), &student_records);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::StudentRecords(student.clone()), 10_000_000, 10_000_000);
-
-        // Incrementar contador
-        env.storage()
-            .instance()
-            .set(&DataKey::RecordCount, &(record_id + 1));
+        let compact = xpuni_common::storage_mode(&env) == StorageMode::Compact;
+        let record_id = RECORDS.append(&env, &student, |record_id| {
+            if compact {
+                StoredRecord::Compact(CompactRecord {
+                    student: student.clone(),
+                    points_spent,
+                    redeemed_at: env.ledger().timestamp(),
+                    content_hash: xpuni_common::content_hash(&env, &reward_name),
+                })
+            } else {
+                StoredRecord::Full(RedemptionRecord {
+                    record_id,
+                    student: student.clone(),
+                    reward_name: reward_name.clone(),
+                    points_spent,
+                    redeemed_at: env.ledger().timestamp(),
+                })
+            }
+        });
+        if compact {
+            env.events()
+                .publish((symbol_short!("content"), record_id), reward_name);
+        }
 
         // Emitir evento on-chain
         env.events()
@@ -108,32 +115,155 @@ impl RedemptionRecords {
 
     /// Retorna el registro de un canje por su ID.
     pub fn get_record(env: Env, record_id: u64) -> RedemptionRecord {
-        env.storage()
-            .persistent()
-            .get(&DataKey::Record(record_id))
-            .unwrap_or_else(|| panic!("record_not_found"))
+        Self::load_record(&env, record_id).unwrap_or_else(|| panic!("record_not_found"))
     }
 
-    /// Retorna todos los record IDs de un estudiante.
-    pub fn get_student_records(env: Env, student: Address) -> Vec<u64> {
-        env.storage()
-            .persistent()
-            .get(&DataKey::StudentRecords(student))
-            .unwrap_or_else(|| Vec::new(&env))
+    /// Indica si `reward_name` es el nombre de la recompensa del canje. En modo
+    /// `Compact` recalcula su hash y lo compara con el guardado; si el canje fue borrado
+    /// (ver `redact`), lo compara con `redacted_hash`.
+    pub fn verify_content(env: Env, record_id: u64, reward_name: String) -> bool {
+        match RECORDS.get(&env, record_id) {
+            Some(StoredRecord::Compact(record)) => {
+                xpuni_common::content_hash(&env, &reward_name) == record.content_hash
+            }
+            Some(StoredRecord::Full(record)) => match Self::redacted_hash(env.clone(), record_id) {
+                Some(hash) => env.crypto().sha256(&reward_name.to_bytes()).to_bytes() == hash,
+                None => record.reward_name == reward_name,
+            },
+            None => false,
+        }
+    }
+
+    /// Retorna hasta `limit` record IDs de un estudiante a partir de la posición `start`,
+    /// en orden de canje.
+    pub fn get_student_records(env: Env, student: Address, start: u32, limit: u32) -> Vec<u64> {
+        RECORDS.ids_of(&env, &student, start, limit)
+    }
+
+    /// Retorna cuántos canjes tiene un estudiante.
+    pub fn student_record_count(env: Env, student: Address) -> u32 {
+        RECORDS.count_of(&env, &student)
     }
 
     /// Retorna el total de canjes registrados.
     pub fn record_count(env: Env) -> u64 {
-        env.storage()
-            .instance()
-            .get(&DataKey::RecordCount)
-            .unwrap_or(0)
+        RECORDS.count(&env)
     }
 
     /// Retorna la dirección del admin.
     pub fn admin(env: Env) -> Address {
-        env.storage().instance().get(&DataKey::Admin).unwrap()
+        xpuni_common::admin(&env)
+    }
+
+    /// Retorna el modo de almacenamiento elegido en el constructor.
+    pub fn storage_mode(env: Env) -> StorageMode {
+        xpuni_common::storage_mode(&env)
+    }
+
+    // ── Recuperación de wallet ────────────────────────────────────────────────
+
+    /// Pasa todos los canjes del estudiante `old`, que perdió su wallet, a `new`, y
+    /// deja registrado el enlace `old → new`. Requiere el rol `Admin`. Retorna la
+    /// cantidad de canjes migrados.
+    pub fn migrate_student(env: Env, caller: Address, old: Address, new: Address) -> u32 {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        xpuni_common::record_migration(&env, &old, &new);
+
+        let moved = RECORDS.move_owner(&env, &old, &new);
+        for record_id in moved.iter() {
+            let stored = match RECORDS.get(&env, record_id).unwrap() {
+                StoredRecord::Full(mut record) => {
+                    record.student = new.clone();
+                    StoredRecord::Full(record)
+                }
+                StoredRecord::Compact(mut record) => {
+                    record.student = new.clone();
+                    StoredRecord::Compact(record)
+                }
+            };
+            RECORDS.replace(&env, record_id, &stored);
+        }
+
+        env.events()
+            .publish((symbol_short!("migrate"), old, new), moved.len());
+
+        moved.len()
+    }
+
+    /// Retorna la dirección a la que se migró el estudiante `old`, si fue migrado.
+    pub fn migrated_to(env: Env, old: Address) -> Option<Address> {
+        xpuni_common::migrated_to(&env, &old)
+    }
+
+    // ── Borrado de datos personales ───────────────────────────────────────────
+
+    /// Reemplaza el `reward_name` de un canje por `[redacted]` y guarda solo su SHA-256
+    /// (ver `redacted_hash`). IDs, puntos y timestamps no cambian. Lo firma el admin, o
+    /// el estudiante del canje junto con el admin. Los canjes en modo `Compact` no
+    /// guardan el nombre.
+    pub fn redact(env: Env, caller: Address, record_id: u64) {
+        let mut record = match RECORDS.get(&env, record_id) {
+            Some(StoredRecord::Full(record)) => record,
+            Some(StoredRecord::Compact(_)) => panic!("nothing_to_redact"),
+            None => panic!("record_not_found"),
+        };
+        xpuni_common::require_redaction_auth(&env, &caller, &record.student);
+        let key = DataKey::Redacted(record_id);
+        if env.storage().persistent().has(&key) {
+            panic!("already_redacted");
+        }
+
+        let hash = xpuni_common::redact(&env, &mut record.reward_name);
+        RECORDS.replace(&env, record_id, &StoredRecord::Full(record));
+        env.storage().persistent().set(&key, &hash);
+        xpuni_common::extend_persistent(&env, &key);
+
+        env.events()
+            .publish((symbol_short!("redacted"), record_id), hash);
+    }
+
+    /// SHA-256 del `reward_name` original de un canje borrado.
+    pub fn redacted_hash(env: Env, record_id: u64) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::Redacted(record_id))
+    }
+
+    // ── Helpers internos ──────────────────────────────────────────────────────
+
+    /// Lee un canje. En modo `Compact`, `reward_name` queda vacío.
+    fn load_record(env: &Env, record_id: u64) -> Option<RedemptionRecord> {
+        let record = match RECORDS.get(env, record_id)? {
+            StoredRecord::Full(record) => record,
+            StoredRecord::Compact(record) => RedemptionRecord {
+                record_id,
+                student: record.student,
+                reward_name: String::from_str(env, ""),
+                points_spent: record.points_spent,
+                redeemed_at: record.redeemed_at,
+            },
+        };
+        Some(record)
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl AccessControl for RedemptionRecords {}
+
+#[contractimpl(contracttrait)]
+impl Pausable for RedemptionRecords {}
+
+impl TimelockHook for RedemptionRecords {
+    fn apply_action(env: &Env, action: TimelockAction) {
+        match action {
+            TimelockAction::SetAdmin(new_admin) => {
+                xpuni_access::transfer_admin(env, &xpuni_common::admin(env), &new_admin);
+                xpuni_common::set_admin(env, &new_admin);
+            }
+            _ => panic!("unsupported_action"),
+        }
     }
 }
 
+#[contractimpl(contracttrait)]
+impl Timelocked for RedemptionRecords {}
+
 mod test;
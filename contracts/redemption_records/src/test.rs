@@ -1,17 +1,19 @@
 #![cfg(test)]
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env, String};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Env, String,
+};
 
-fn setup(env: &Env) -> (RedemptionRecordsClient, Address) {
-    let contract_id = env.register(RedemptionRecords, ());
-    let client = RedemptionRecordsClient::new(env, &contract_id);
+fn setup(env: &Env) -> (RedemptionRecordsClient<'_>, Address) {
     let admin = Address::generate(env);
-    client.initialize(&admin);
+    let contract_id = env.register(RedemptionRecords, (&admin, StorageMode::Full));
+    let client = RedemptionRecordsClient::new(env, &contract_id);
     (client, admin)
 }
 
 #[test]
-fn test_initialize() {
+fn test_constructor() {
     let env = Env::default();
     env.mock_all_auths();
     let (client, admin) = setup(&env);
@@ -20,24 +22,16 @@ fn test_initialize() {
     assert_eq!(client.record_count(), 0);
 }
 
-#[test]
-#[should_panic(expected = "already_initialized")]
-fn test_double_initialize_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let (client, admin) = setup(&env);
-    client.initialize(&admin);
-}
-
 #[test]
 fn test_record_and_get() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let student = Address::generate(&env);
 
     let record_id = client.record_redemption(
+        &admin,
         &student,
         &String::from_str(&env, "Pizza de pepperoni"),
         &150_i128,
@@ -57,16 +51,18 @@ fn test_record_and_get() {
 fn test_multiple_records_increment_correctly() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let student = Address::generate(&env);
 
     let id0 = client.record_redemption(
+        &admin,
         &student,
         &String::from_str(&env, "Bono de nota"),
         &200_i128,
     );
     let id1 = client.record_redemption(
+        &admin,
         &student,
         &String::from_str(&env, "Libro de Rust"),
         &100_i128,
@@ -81,17 +77,17 @@ fn test_multiple_records_increment_correctly() {
 fn test_get_student_records() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let s1 = Address::generate(&env);
     let s2 = Address::generate(&env);
 
-    client.record_redemption(&s1, &String::from_str(&env, "Premio A"), &50_i128);
-    client.record_redemption(&s2, &String::from_str(&env, "Premio B"), &75_i128);
-    client.record_redemption(&s1, &String::from_str(&env, "Premio C"), &100_i128);
+    client.record_redemption(&admin, &s1, &String::from_str(&env, "Premio A"), &50_i128);
+    client.record_redemption(&admin, &s2, &String::from_str(&env, "Premio B"), &75_i128);
+    client.record_redemption(&admin, &s1, &String::from_str(&env, "Premio C"), &100_i128);
 
-    let s1_records = client.get_student_records(&s1);
-    let s2_records = client.get_student_records(&s2);
+    let s1_records = client.get_student_records(&s1, &0, &10);
+    let s2_records = client.get_student_records(&s2, &0, &10);
 
     assert_eq!(s1_records.len(), 2);
     assert_eq!(s2_records.len(), 1);
@@ -107,10 +103,28 @@ fn test_get_student_records_empty() {
     let (client, _) = setup(&env);
 
     let student = Address::generate(&env);
-    let records = client.get_student_records(&student);
+    let records = client.get_student_records(&student, &0, &10);
     assert_eq!(records.len(), 0);
 }
 
+#[test]
+fn test_get_student_records_paginated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Cafe");
+    for _ in 0..5 {
+        client.record_redemption(&admin, &student, &reward, &10_i128);
+    }
+
+    assert_eq!(client.student_record_count(&student), 5);
+    assert_eq!(client.get_student_records(&student, &1, &2), vec![&env, 1_u64, 2_u64]);
+    assert_eq!(client.get_student_records(&student, &4, &10), vec![&env, 4_u64]);
+    assert_eq!(client.get_student_records(&student, &5, &10).len(), 0);
+}
+
 #[test]
 #[should_panic(expected = "record_not_found")]
 fn test_get_nonexistent_record() {
@@ -119,3 +133,130 @@ fn test_get_nonexistent_record() {
     let (client, _) = setup(&env);
     client.get_record(&999_u64);
 }
+
+#[test]
+fn test_set_admin_through_timelock() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin.clone()), &delay);
+    assert_eq!(client.pending_actions().len(), 1);
+    assert_eq!(client.admin(), admin);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+
+    assert_eq!(client.admin(), new_admin);
+    assert_eq!(client.pending_actions().len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "missing_role")]
+fn test_record_requires_partner_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let partner = Address::generate(&env);
+
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Libro de Rust");
+
+    client.grant_role(&admin, &Role::Partner, &partner);
+    client.record_redemption(&partner, &student, &reward, &10_i128);
+    client.revoke_role(&admin, &Role::Partner, &partner);
+    client.record_redemption(&partner, &student, &reward, &10_i128);
+}
+
+#[test]
+fn test_migrate_student_moves_records() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+    let reward = String::from_str(&env, "Pizza de pepperoni");
+
+    let first = client.record_redemption(&admin, &old, &reward, &150_i128);
+    let second = client.record_redemption(&admin, &old, &reward, &90_i128);
+
+    assert_eq!(client.migrate_student(&admin, &old, &new), 2);
+
+    assert_eq!(client.student_record_count(&old), 0);
+    assert_eq!(client.get_student_records(&new, &0, &10), vec![&env, first, second]);
+    assert_eq!(client.get_record(&second).student, new);
+    assert_eq!(client.migrated_to(&old), Some(new));
+}
+
+#[test]
+fn test_student_redacts_record_with_admin_cosign() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Consulta con psicología");
+
+    let record_id = client.record_redemption(&admin, &student, &reward, &150_i128);
+    client.redact(&student, &record_id);
+
+    let signers: Vec<Address> = Vec::from_iter(&env, env.auths().into_iter().map(|(a, _)| a));
+    assert!(signers.contains(&student) && signers.contains(&admin));
+
+    let record = client.get_record(&record_id);
+    assert_eq!(record.reward_name, String::from_str(&env, "[redacted]"));
+    assert_eq!(record.points_spent, 150);
+    assert_eq!(record.student, student);
+    assert_eq!(
+        client.redacted_hash(&record_id),
+        Some(env.crypto().sha256(&reward.to_bytes()).to_bytes())
+    );
+}
+
+#[test]
+fn test_verify_content_after_redaction() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Consulta con psicología");
+
+    let record_id = client.record_redemption(&admin, &student, &reward, &150_i128);
+    client.redact(&admin, &record_id);
+
+    assert!(client.verify_content(&record_id, &reward));
+    assert!(!client.verify_content(&record_id, &String::from_str(&env, "[redacted]")));
+}
+
+#[test]
+#[should_panic(expected = "not_authorized")]
+fn test_redact_requires_admin_or_student() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Pizza de pepperoni");
+
+    let record_id = client.record_redemption(&admin, &student, &reward, &150_i128);
+    client.redact(&Address::generate(&env), &record_id);
+}
+
+#[test]
+fn test_compact_mode_keeps_only_content_hash() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(RedemptionRecords, (&admin, StorageMode::Compact));
+    let client = RedemptionRecordsClient::new(&env, &contract_id);
+    let student = Address::generate(&env);
+    let reward = String::from_str(&env, "Pizza de pepperoni");
+
+    let record_id = client.record_redemption(&admin, &student, &reward, &150_i128);
+
+    let record = client.get_record(&record_id);
+    assert_eq!(record.reward_name, String::from_str(&env, ""));
+    assert_eq!(record.points_spent, 150);
+    assert!(client.verify_content(&record_id, &reward));
+    assert!(!client.verify_content(&record_id, &String::from_str(&env, "Pizza hawaiana")));
+}
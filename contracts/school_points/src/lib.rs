@@ -1,5 +1,9 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, String};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, symbol_short, Address, Env, String, Symbol, Vec,
+};
+use xpuni_access::{AccessControl, Pausable, Role};
+use xpuni_timelock::{QueuedOperation, TimelockAction, TimelockHook, Timelocked};
 
 // ── Allowance types ───────────────────────────────────────────────────────────
 
@@ -26,7 +30,13 @@ pub enum DataKey {
     Name,
     Symbol,
     TotalSupply,
+    /// Saldo total del holder (suma de todas sus categorías).
     Balance(Address),
+    /// Saldo del holder en una categoría concreta (p. ej. `academic`, `sports`).
+    CategoryBalance(Address, Symbol),
+    /// Categorías con las que el holder ha recibido puntos, en orden de llegada.
+    /// Es el orden por defecto en que se descuentan los gastos.
+    Categories(Address),
     Allowance(AllowanceKey),
 }
 
@@ -36,13 +46,12 @@ pub enum DataKey {
 pub struct SchoolPoints;
 
 #[contractimpl]
+#[allow(deprecated)]
 impl SchoolPoints {
-    /// Inicializa el contrato. Solo puede llamarse una vez.
-    pub fn initialize(env: Env, admin: Address, name: String, symbol: String) {
-        if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already_initialized");
-        }
+    /// Constructor: fija admin, nombre y símbolo de forma atómica con el deploy.
+    pub fn __constructor(env: Env, admin: Address, name: String, symbol: String) {
         env.storage().instance().set(&DataKey::Admin, &admin);
+        xpuni_access::init(&env, &admin);
         env.storage().instance().set(&DataKey::Name, &name);
         env.storage().instance().set(&DataKey::Symbol, &symbol);
         env.storage().instance().set(&DataKey::TotalSupply, &0_i128);
@@ -72,6 +81,7 @@ impl SchoolPoints {
 
     // ── SEP-41: Balance y Allowance ───────────────────────────────────────────
 
+    /// Saldo total del holder: la suma de sus saldos por categoría.
     pub fn balance(env: Env, id: Address) -> i128 {
         env.storage()
             .persistent()
@@ -79,6 +89,22 @@ impl SchoolPoints {
             .unwrap_or(0)
     }
 
+    /// Saldo del holder en una sola categoría.
+    pub fn balance_of_category(env: Env, id: Address, category: Symbol) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::CategoryBalance(id, category))
+            .unwrap_or(0)
+    }
+
+    /// Categorías en las que el holder tiene o ha tenido saldo, en orden de llegada.
+    pub fn categories_of(env: Env, id: Address) -> Vec<Symbol> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Categories(id))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
     pub fn allowance(env: Env, from: Address, spender: Address) -> i128 {
         let key = DataKey::Allowance(AllowanceKey { from, spender });
         match env
@@ -99,43 +125,36 @@ impl SchoolPoints {
 
     // ── SEP-41: Operaciones mutantes ──────────────────────────────────────────
 
-    /// Acuña puntos a una dirección. Solo el admin puede llamar esto.
-    pub fn mint(env: Env, to: Address, amount: i128) {
+    /// Acuña puntos de una categoría a una dirección. Requiere el rol `Minter`.
+    pub fn mint(env: Env, minter: Address, to: Address, amount: i128, category: Symbol) {
         if amount <= 0 {
             panic!("amount_must_be_positive");
         }
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+        xpuni_access::require_not_paused(&env);
+        xpuni_access::require_role(&env, &minter, Role::Minter);
 
-        let bal_key = DataKey::Balance(to.clone());
-        let current: i128 = env.storage().persistent().get(&bal_key).unwrap_or(0);
-        env.storage().persistent().set(&bal_key, &(current + amount));
+        Self::credit(&env, &to, &category, amount);
 
         let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
         env.storage().instance().set(&DataKey::TotalSupply, &(supply + amount));
 
         env.events()
-            .publish((symbol_short!("mint"), admin, to), amount);
+            .publish((symbol_short!("mint"), minter, to), amount);
     }
 
     /// SEP-41 burn: el holder quema sus propios tokens. Requiere from.require_auth().
     pub fn burn(env: Env, from: Address, amount: i128) {
-        if amount <= 0 {
-            panic!("amount_must_be_positive");
-        }
         from.require_auth();
+        xpuni_access::require_not_paused(&env);
+        let order = Self::categories_of(env.clone(), from.clone());
+        Self::burn_categories(&env, &from, amount, &order);
+    }
 
-        let key = DataKey::Balance(from.clone());
-        let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
-        if current < amount {
-            panic!("insufficient_balance");
-        }
-        env.storage().persistent().set(&key, &(current - amount));
-
-        let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-        env.storage().instance().set(&DataKey::TotalSupply, &(supply - amount));
-
-        env.events().publish((symbol_short!("burn"), from), amount);
+    /// Como `burn`, pero descontando solo de `categories` y en ese orden de prioridad.
+    pub fn burn_by_category(env: Env, from: Address, amount: i128, categories: Vec<Symbol>) {
+        from.require_auth();
+        xpuni_access::require_not_paused(&env);
+        Self::burn_categories(&env, &from, amount, &categories);
     }
 
     /// SEP-41 burn_from: un spender quema tokens en nombre de `from` consumiendo allowance.
@@ -144,96 +163,133 @@ impl SchoolPoints {
             panic!("amount_must_be_positive");
         }
         spender.require_auth();
+        xpuni_access::require_not_paused(&env);
+        Self::spend_allowance(&env, &from, &spender, amount);
 
-        let allow_key = DataKey::Allowance(AllowanceKey {
-            from: from.clone(),
-            spender: spender.clone(),
-        });
-        let av: AllowanceValue = env
-            .storage()
-            .temporary()
-            .get(&allow_key)
-            .unwrap_or(AllowanceValue { amount: 0, expiration_ledger: 0 });
-
-        if env.ledger().sequence() > av.expiration_ledger {
-            panic!("allowance_expired");
-        }
-        if av.amount < amount {
-            panic!("insufficient_allowance");
-        }
+        let order = Self::categories_of(env.clone(), from.clone());
+        Self::burn_categories(&env, &from, amount, &order);
+    }
 
-        env.storage().temporary().set(
-            &allow_key,
-            &AllowanceValue {
-                amount: av.amount - amount,
-                expiration_ledger: av.expiration_ledger,
-            },
-        );
+    /// Clawback para redenciones: quema tokens de cualquier dirección.
+    /// Requiere el rol `Partner`.
+    pub fn clawback(env: Env, partner: Address, from: Address, amount: i128) {
+        xpuni_access::require_not_paused(&env);
+        xpuni_access::require_role(&env, &partner, Role::Partner);
+        let order = Self::categories_of(env.clone(), from.clone());
+        Self::burn_categories(&env, &from, amount, &order);
+    }
 
-        let key = DataKey::Balance(from.clone());
-        let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
-        if current < amount {
-            panic!("insufficient_balance");
-        }
-        env.storage().persistent().set(&key, &(current - amount));
+    /// Clawback restringido a `categories`, en ese orden de prioridad.
+    /// Permite a un partner aceptar solo ciertas categorías (p. ej. solo `academic`).
+    pub fn clawback_by_category(
+        env: Env,
+        partner: Address,
+        from: Address,
+        amount: i128,
+        categories: Vec<Symbol>,
+    ) {
+        xpuni_access::require_not_paused(&env);
+        xpuni_access::require_role(&env, &partner, Role::Partner);
+        Self::burn_categories(&env, &from, amount, &categories);
+    }
 
-        let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-        env.storage().instance().set(&DataKey::TotalSupply, &(supply - amount));
+    /// SEP-41 transfer: requiere from.require_auth().
+    /// Los puntos conservan su categoría al llegar a `to`.
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        xpuni_access::require_not_paused(&env);
+        let order = Self::categories_of(env.clone(), from.clone());
+        Self::transfer_categories(&env, &from, &to, amount, &order);
+    }
 
-        env.events().publish((symbol_short!("burn"), from), amount);
+    /// Como `transfer`, pero moviendo solo puntos de `categories` y en ese orden.
+    pub fn transfer_by_category(
+        env: Env,
+        from: Address,
+        to: Address,
+        amount: i128,
+        categories: Vec<Symbol>,
+    ) {
+        from.require_auth();
+        xpuni_access::require_not_paused(&env);
+        Self::transfer_categories(&env, &from, &to, amount, &categories);
     }
 
-    /// Admin clawback: el admin quema tokens de cualquier dirección. Para redenciones.
-    pub fn clawback(env: Env, from: Address, amount: i128) {
+    /// SEP-41 transfer_from: un spender transfiere en nombre de `from` consumiendo allowance.
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
         if amount <= 0 {
             panic!("amount_must_be_positive");
         }
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-
-        let key = DataKey::Balance(from.clone());
-        let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
-        if current < amount {
-            panic!("insufficient_balance");
-        }
-        env.storage().persistent().set(&key, &(current - amount));
-
-        let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
-        env.storage().instance().set(&DataKey::TotalSupply, &(supply - amount));
+        spender.require_auth();
+        xpuni_access::require_not_paused(&env);
+        Self::spend_allowance(&env, &from, &spender, amount);
 
-        env.events().publish((symbol_short!("burn"), from), amount);
+        let order = Self::categories_of(env.clone(), from.clone());
+        Self::transfer_categories(&env, &from, &to, amount, &order);
     }
 
-    /// SEP-41 transfer: requiere from.require_auth().
-    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
-        if amount <= 0 {
-            panic!("amount_must_be_positive");
-        }
+    /// SEP-41 approve: otorga a `spender` permiso de gastar `amount` tokens de `from`.
+    pub fn approve(
+        env: Env,
+        from: Address,
+        spender: Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
         from.require_auth();
 
-        let from_key = DataKey::Balance(from.clone());
-        let from_balance: i128 = env.storage().persistent().get(&from_key).unwrap_or(0);
-        if from_balance < amount {
-            panic!("insufficient_balance");
+        if expiration_ledger < env.ledger().sequence() {
+            panic!("expiration_ledger_in_past");
         }
 
-        let to_key = DataKey::Balance(to.clone());
-        let to_balance: i128 = env.storage().persistent().get(&to_key).unwrap_or(0);
+        let key = DataKey::Allowance(AllowanceKey {
+            from: from.clone(),
+            spender: spender.clone(),
+        });
 
-        env.storage().persistent().set(&from_key, &(from_balance - amount));
-        env.storage().persistent().set(&to_key, &(to_balance + amount));
+        env.storage()
+            .temporary()
+            .set(&key, &AllowanceValue { amount, expiration_ledger });
+
+        let ledgers_until_expiry = expiration_ledger.saturating_sub(env.ledger().sequence());
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, ledgers_until_expiry, ledgers_until_expiry);
 
         env.events()
-            .publish((symbol_short!("transfer"), from, to), amount);
+            .publish((symbol_short!("approve"), from, spender), (amount, expiration_ledger));
     }
 
-    /// SEP-41 transfer_from: un spender transfiere en nombre de `from` consumiendo allowance.
-    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
-        if amount <= 0 {
-            panic!("amount_must_be_positive");
+    // ── Recuperación de wallet ────────────────────────────────────────────────
+
+    /// Pasa todo el saldo del estudiante `old`, que perdió su wallet, a `new`, categoría
+    /// por categoría, y deja registrado el enlace `old → new`. No requiere la firma de
+    /// `old`; sí el rol `Admin`. Retorna los puntos migrados.
+    pub fn migrate_student(env: Env, caller: Address, old: Address, new: Address) -> i128 {
+        xpuni_access::require_role(&env, &caller, Role::Admin);
+        xpuni_common::record_migration(&env, &old, &new);
+
+        let amount = Self::balance(env.clone(), old.clone());
+        if amount > 0 {
+            let order = Self::categories_of(env.clone(), old.clone());
+            Self::transfer_categories(&env, &old, &new, amount, &order);
         }
-        spender.require_auth();
 
+        env.events()
+            .publish((symbol_short!("migrate"), old, new), amount);
+
+        amount
+    }
+
+    /// Retorna la dirección a la que se migró el estudiante `old`, si fue migrado.
+    pub fn migrated_to(env: Env, old: Address) -> Option<Address> {
+        xpuni_common::migrated_to(&env, &old)
+    }
+
+    // ── Helpers internos ──────────────────────────────────────────────────────
+
+    /// Consume `amount` del allowance de `spender` sobre `from`.
+    fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
         let allow_key = DataKey::Allowance(AllowanceKey {
             from: from.clone(),
             spender: spender.clone(),
@@ -258,61 +314,111 @@ impl SchoolPoints {
                 expiration_ledger: av.expiration_ledger,
             },
         );
+    }
+
+    /// Suma `amount` al saldo de `to` en `category` y a su saldo total.
+    fn credit(env: &Env, to: &Address, category: &Symbol, amount: i128) {
+        let cat_key = DataKey::CategoryBalance(to.clone(), category.clone());
+        let cat_balance: i128 = env.storage().persistent().get(&cat_key).unwrap_or(0);
+        env.storage().persistent().set(&cat_key, &(cat_balance + amount));
+
+        let cats_key = DataKey::Categories(to.clone());
+        let mut cats: Vec<Symbol> = env
+            .storage()
+            .persistent()
+            .get(&cats_key)
+            .unwrap_or_else(|| Vec::new(env));
+        if !cats.contains(category) {
+            cats.push_back(category.clone());
+            env.storage().persistent().set(&cats_key, &cats);
+        }
+
+        let bal_key = DataKey::Balance(to.clone());
+        let current: i128 = env.storage().persistent().get(&bal_key).unwrap_or(0);
+        env.storage().persistent().set(&bal_key, &(current + amount));
+    }
+
+    /// Descuenta `amount` de `from` recorriendo `order` y agotando cada categoría antes
+    /// de pasar a la siguiente. Retorna lo descontado por categoría, en el mismo orden.
+    fn debit(env: &Env, from: &Address, amount: i128, order: &Vec<Symbol>) -> Vec<(Symbol, i128)> {
+        if amount <= 0 {
+            panic!("amount_must_be_positive");
+        }
 
-        let from_key = DataKey::Balance(from.clone());
-        let from_balance: i128 = env.storage().persistent().get(&from_key).unwrap_or(0);
-        if from_balance < amount {
+        let bal_key = DataKey::Balance(from.clone());
+        let current: i128 = env.storage().persistent().get(&bal_key).unwrap_or(0);
+        if current < amount {
             panic!("insufficient_balance");
         }
 
-        let to_key = DataKey::Balance(to.clone());
-        let to_balance: i128 = env.storage().persistent().get(&to_key).unwrap_or(0);
+        let mut remaining = amount;
+        let mut taken: Vec<(Symbol, i128)> = Vec::new(env);
+        for category in order.iter() {
+            if remaining == 0 {
+                break;
+            }
+            let cat_key = DataKey::CategoryBalance(from.clone(), category.clone());
+            let cat_balance: i128 = env.storage().persistent().get(&cat_key).unwrap_or(0);
+            let take = if cat_balance < remaining { cat_balance } else { remaining };
+            if take > 0 {
+                env.storage().persistent().set(&cat_key, &(cat_balance - take));
+                taken.push_back((category, take));
+                remaining -= take;
+            }
+        }
+        if remaining > 0 {
+            panic!("insufficient_category_balance");
+        }
+
+        env.storage().persistent().set(&bal_key, &(current - amount));
+        taken
+    }
 
-        env.storage().persistent().set(&from_key, &(from_balance - amount));
-        env.storage().persistent().set(&to_key, &(to_balance + amount));
+    fn burn_categories(env: &Env, from: &Address, amount: i128, order: &Vec<Symbol>) {
+        Self::debit(env, from, amount, order);
 
-        env.events()
-            .publish((symbol_short!("transfer"), from, to), amount);
+        let supply: i128 = env.storage().instance().get(&DataKey::TotalSupply).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalSupply, &(supply - amount));
+
+        env.events().publish((symbol_short!("burn"), from.clone()), amount);
     }
 
-    /// SEP-41 approve: otorga a `spender` permiso de gastar `amount` tokens de `from`.
-    pub fn approve(
-        env: Env,
-        from: Address,
-        spender: Address,
+    fn transfer_categories(
+        env: &Env,
+        from: &Address,
+        to: &Address,
         amount: i128,
-        expiration_ledger: u32,
+        order: &Vec<Symbol>,
     ) {
-        from.require_auth();
-
-        if expiration_ledger < env.ledger().sequence() {
-            panic!("expiration_ledger_in_past");
+        for (category, moved) in Self::debit(env, from, amount, order).iter() {
+            Self::credit(env, to, &category, moved);
         }
 
-        let key = DataKey::Allowance(AllowanceKey {
-            from: from.clone(),
-            spender: spender.clone(),
-        });
-
-        env.storage()
-            .temporary()
-            .set(&key, &AllowanceValue { amount, expiration_ledger });
-
-        let ledgers_until_expiry = expiration_ledger.saturating_sub(env.ledger().sequence());
-        env.storage()
-            .temporary()
-            .extend_ttl(&key, ledgers_until_expiry, ledgers_until_expiry);
-
         env.events()
-            .publish((symbol_short!("approve"), from, spender), (amount, expiration_ledger));
+            .publish((symbol_short!("transfer"), from.clone(), to.clone()), amount);
     }
+}
 
-    /// Transfiere el rol de admin a una nueva dirección. Requiere auth del admin actual.
-    pub fn set_admin(env: Env, new_admin: Address) {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
+#[contractimpl(contracttrait)]
+impl AccessControl for SchoolPoints {}
+
+#[contractimpl(contracttrait)]
+impl Pausable for SchoolPoints {}
+
+impl TimelockHook for SchoolPoints {
+    fn apply_action(env: &Env, action: TimelockAction) {
+        match action {
+            TimelockAction::SetAdmin(new_admin) => {
+                let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+                xpuni_access::transfer_admin(env, &admin, &new_admin);
+                env.storage().instance().set(&DataKey::Admin, &new_admin);
+            }
+            _ => panic!("unsupported_action"),
+        }
     }
 }
 
+#[contractimpl(contracttrait)]
+impl Timelocked for SchoolPoints {}
+
 mod test;
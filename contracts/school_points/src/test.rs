@@ -1,21 +1,27 @@
 #![cfg(test)]
 use super::*;
-use soroban_sdk::{testutils::Address as _, Env, String};
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Ledger},
+    vec, Env, String,
+};
 
-fn setup(env: &Env) -> (SchoolPointsClient, Address) {
-    let contract_id = env.register(SchoolPoints, ());
-    let client = SchoolPointsClient::new(env, &contract_id);
+fn setup(env: &Env) -> (SchoolPointsClient<'_>, Address) {
     let admin = Address::generate(env);
-    client.initialize(
-        &admin,
-        &String::from_str(env, "School Points"),
-        &String::from_str(env, "SPTS"),
+    let contract_id = env.register(
+        SchoolPoints,
+        (
+            &admin,
+            String::from_str(env, "School Points"),
+            String::from_str(env, "SPTS"),
+        ),
     );
+    let client = SchoolPointsClient::new(env, &contract_id);
     (client, admin)
 }
 
 #[test]
-fn test_initialize() {
+fn test_constructor() {
     let env = Env::default();
     env.mock_all_auths();
     let (client, admin) = setup(&env);
@@ -27,32 +33,19 @@ fn test_initialize() {
     assert_eq!(client.total_supply(), 0);
 }
 
-#[test]
-#[should_panic(expected = "already_initialized")]
-fn test_double_initialize_fails() {
-    let env = Env::default();
-    env.mock_all_auths();
-    let (client, admin) = setup(&env);
-    client.initialize(
-        &admin,
-        &String::from_str(&env, "School Points"),
-        &String::from_str(&env, "SPTS"),
-    );
-}
-
 #[test]
 fn test_mint_and_balance() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let student = Address::generate(&env);
 
     assert_eq!(client.balance(&student), 0);
-    client.mint(&student, &100_i128);
+    client.mint(&admin, &student, &100_i128, &symbol_short!("academic"));
     assert_eq!(client.balance(&student), 100);
     assert_eq!(client.total_supply(), 100);
 
-    client.mint(&student, &50_i128);
+    client.mint(&admin, &student, &50_i128, &symbol_short!("academic"));
     assert_eq!(client.balance(&student), 150);
     assert_eq!(client.total_supply(), 150);
 }
@@ -61,10 +54,10 @@ fn test_mint_and_balance() {
 fn test_burn_by_holder() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let student = Address::generate(&env);
 
-    client.mint(&student, &200_i128);
+    client.mint(&admin, &student, &200_i128, &symbol_short!("academic"));
     client.burn(&student, &80_i128);
     assert_eq!(client.balance(&student), 120);
     assert_eq!(client.total_supply(), 120);
@@ -75,10 +68,10 @@ fn test_burn_by_holder() {
 fn test_burn_insufficient_balance() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let student = Address::generate(&env);
 
-    client.mint(&student, &50_i128);
+    client.mint(&admin, &student, &50_i128, &symbol_short!("academic"));
     client.burn(&student, &100_i128);
 }
 
@@ -86,11 +79,11 @@ fn test_burn_insufficient_balance() {
 fn test_clawback_by_admin() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let student = Address::generate(&env);
 
-    client.mint(&student, &200_i128);
-    client.clawback(&student, &80_i128);
+    client.mint(&admin, &student, &200_i128, &symbol_short!("academic"));
+    client.clawback(&admin, &student, &80_i128);
     assert_eq!(client.balance(&student), 120);
     assert_eq!(client.total_supply(), 120);
 }
@@ -100,22 +93,22 @@ fn test_clawback_by_admin() {
 fn test_clawback_insufficient_balance() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let student = Address::generate(&env);
 
-    client.mint(&student, &50_i128);
-    client.clawback(&student, &100_i128);
+    client.mint(&admin, &student, &50_i128, &symbol_short!("academic"));
+    client.clawback(&admin, &student, &100_i128);
 }
 
 #[test]
 fn test_transfer() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let s1 = Address::generate(&env);
     let s2 = Address::generate(&env);
 
-    client.mint(&s1, &100_i128);
+    client.mint(&admin, &s1, &100_i128, &symbol_short!("academic"));
     client.transfer(&s1, &s2, &40_i128);
 
     assert_eq!(client.balance(&s1), 60);
@@ -127,13 +120,13 @@ fn test_transfer() {
 fn test_approve_and_transfer_from() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let owner = Address::generate(&env);
     let spender = Address::generate(&env);
     let recipient = Address::generate(&env);
 
-    client.mint(&owner, &100_i128);
+    client.mint(&admin, &owner, &100_i128, &symbol_short!("academic"));
     client.approve(&owner, &spender, &50_i128, &(env.ledger().sequence() + 1000));
     assert_eq!(client.allowance(&owner, &spender), 50);
 
@@ -147,12 +140,12 @@ fn test_approve_and_transfer_from() {
 fn test_approve_and_burn_from() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
 
     let owner = Address::generate(&env);
     let spender = Address::generate(&env);
 
-    client.mint(&owner, &100_i128);
+    client.mint(&admin, &owner, &100_i128, &symbol_short!("academic"));
     client.approve(&owner, &spender, &60_i128, &(env.ledger().sequence() + 1000));
 
     client.burn_from(&spender, &owner, &40_i128);
@@ -162,14 +155,74 @@ fn test_approve_and_burn_from() {
 }
 
 #[test]
-fn test_set_admin() {
+fn test_set_admin_through_timelock() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
     let new_admin = Address::generate(&env);
 
-    client.set_admin(&new_admin);
+    let delay = client.min_delay();
+    let op_id =
+        client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin.clone()), &delay);
+    assert_eq!(client.pending_actions().len(), 1);
+    assert_eq!(client.admin(), admin);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op_id);
+
     assert_eq!(client.admin(), new_admin);
+    assert!(client.has_role(&Role::Admin, &new_admin));
+    assert!(!client.has_role(&Role::Admin, &admin));
+    assert_eq!(client.pending_actions().len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "operation_not_ready")]
+fn test_timelock_execute_before_delay() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay();
+    let op_id = client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin), &delay);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay - 1);
+    client.execute_action(&op_id);
+}
+
+#[test]
+#[should_panic(expected = "delay_below_minimum")]
+fn test_timelock_delay_below_minimum() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let new_admin = Address::generate(&env);
+
+    let delay = client.min_delay() - 1;
+    client.queue_action(&admin, &TimelockAction::SetAdmin(new_admin), &delay);
+}
+
+#[test]
+fn test_timelock_cancel() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+
+    let delay = client.min_delay();
+    let other = Address::generate(&env);
+    let op0 = client.queue_action(&admin, &TimelockAction::SetAdmin(other), &delay);
+    let op1 = client.queue_action(&admin, &TimelockAction::SetMinDelay(delay * 2), &delay);
+    client.cancel_action(&admin, &op0);
+
+    let pending = client.pending_actions();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap().op_id, op1);
+
+    env.ledger().with_mut(|l| l.sequence_number += delay);
+    client.execute_action(&op1);
+    assert_eq!(client.min_delay(), delay * 2);
+    assert_eq!(client.admin(), admin);
 }
 
 #[test]
@@ -177,7 +230,213 @@ fn test_set_admin() {
 fn test_mint_negative_amount() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, _) = setup(&env);
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+    client.mint(&admin, &student, &-10_i128, &symbol_short!("academic"));
+}
+
+#[test]
+fn test_mint_tracks_categories() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.mint(&admin, &student, &100_i128, &symbol_short!("academic"));
+    client.mint(&admin, &student, &30_i128, &symbol_short!("sports"));
+    client.mint(&admin, &student, &20_i128, &symbol_short!("academic"));
+
+    assert_eq!(client.balance(&student), 150);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("academic")), 120);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("sports")), 30);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("volunteer")), 0);
+    assert_eq!(
+        client.categories_of(&student),
+        vec![&env, symbol_short!("academic"), symbol_short!("sports")]
+    );
+}
+
+#[test]
+fn test_burn_uses_arrival_order_by_default() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.mint(&admin, &student, &50_i128, &symbol_short!("sports"));
+    client.mint(&admin, &student, &50_i128, &symbol_short!("academic"));
+    client.burn(&student, &70_i128);
+
+    assert_eq!(client.balance(&student), 30);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("sports")), 0);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("academic")), 30);
+}
+
+#[test]
+fn test_clawback_by_category_priority() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.mint(&admin, &student, &40_i128, &symbol_short!("sports"));
+    client.mint(&admin, &student, &40_i128, &symbol_short!("academic"));
+    client.mint(&admin, &student, &40_i128, &symbol_short!("volunteer"));
+
+    client.clawback_by_category(
+        &admin,
+        &student,
+        &60_i128,
+        &vec![&env, symbol_short!("volunteer"), symbol_short!("academic")],
+    );
+
+    assert_eq!(client.balance(&student), 60);
+    assert_eq!(client.total_supply(), 60);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("volunteer")), 0);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("academic")), 20);
+    assert_eq!(client.balance_of_category(&student, &symbol_short!("sports")), 40);
+}
+
+#[test]
+#[should_panic(expected = "insufficient_category_balance")]
+fn test_clawback_by_category_rejects_other_categories() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let student = Address::generate(&env);
+
+    client.mint(&admin, &student, &40_i128, &symbol_short!("academic"));
+    client.mint(&admin, &student, &100_i128, &symbol_short!("sports"));
+
+    let academic_only = vec![&env, symbol_short!("academic")];
+    client.clawback_by_category(&admin, &student, &50_i128, &academic_only);
+}
+
+#[test]
+fn test_transfer_keeps_categories() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let s1 = Address::generate(&env);
+    let s2 = Address::generate(&env);
+
+    client.mint(&admin, &s1, &30_i128, &symbol_short!("academic"));
+    client.mint(&admin, &s1, &30_i128, &symbol_short!("sports"));
+    client.transfer_by_category(
+        &s1,
+        &s2,
+        &40_i128,
+        &vec![&env, symbol_short!("sports"), symbol_short!("academic")],
+    );
+
+    assert_eq!(client.balance(&s2), 40);
+    assert_eq!(client.balance_of_category(&s2, &symbol_short!("sports")), 30);
+    assert_eq!(client.balance_of_category(&s2, &symbol_short!("academic")), 10);
+    assert_eq!(client.balance_of_category(&s1, &symbol_short!("academic")), 20);
+}
+
+#[test]
+fn test_roles_can_be_delegated() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let minter = Address::generate(&env);
     let student = Address::generate(&env);
-    client.mint(&student, &-10_i128);
+
+    assert!(client.has_role(&Role::Minter, &admin));
+    assert!(!client.has_role(&Role::Minter, &minter));
+
+    client.grant_role(&admin, &Role::Minter, &minter);
+    client.mint(&minter, &student, &10_i128, &symbol_short!("sports"));
+    assert_eq!(client.balance(&student), 10);
+
+    client.renounce_role(&minter, &Role::Minter);
+    assert!(!client.has_role(&Role::Minter, &minter));
+}
+
+#[test]
+#[should_panic(expected = "missing_role")]
+fn test_mint_without_minter_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let minter = Address::generate(&env);
+
+    client.grant_role(&admin, &Role::Minter, &minter);
+    client.revoke_role(&admin, &Role::Minter, &minter);
+    client.mint(&minter, &Address::generate(&env), &10_i128, &symbol_short!("sports"));
+}
+
+#[test]
+fn test_role_admin_hierarchy() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let partner_manager = Address::generate(&env);
+    let partner = Address::generate(&env);
+
+    // Los `Pauser` pasan a administrar el rol `Partner`.
+    client.set_role_admin(&admin, &Role::Partner, &Role::Pauser);
+    assert_eq!(client.role_admin(&Role::Partner), Role::Pauser);
+
+    client.grant_role(&admin, &Role::Pauser, &partner_manager);
+    client.grant_role(&partner_manager, &Role::Partner, &partner);
+    assert!(client.has_role(&Role::Partner, &partner));
+}
+
+#[test]
+#[should_panic(expected = "admin_role_is_timelocked")]
+fn test_admin_role_cannot_be_granted_directly() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    client.grant_role(&admin, &Role::Admin, &Address::generate(&env));
+}
+
+#[test]
+#[should_panic(expected = "contract_paused")]
+fn test_pause_blocks_transfers() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let s1 = Address::generate(&env);
+
+    client.mint(&admin, &s1, &100_i128, &symbol_short!("academic"));
+    client.pause(&admin);
+    assert!(client.paused());
+    client.transfer(&s1, &Address::generate(&env), &10_i128);
+}
+
+#[test]
+fn test_migrate_student_moves_balance_by_category() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+    let new = Address::generate(&env);
+
+    client.mint(&admin, &old, &100, &symbol_short!("academic"));
+    client.mint(&admin, &old, &40, &symbol_short!("sports"));
+    client.mint(&admin, &new, &5, &symbol_short!("sports"));
+
+    assert_eq!(client.migrate_student(&admin, &old, &new), 140);
+
+    assert_eq!(client.balance(&old), 0);
+    assert_eq!(client.balance(&new), 145);
+    assert_eq!(client.balance_of_category(&new, &symbol_short!("academic")), 100);
+    assert_eq!(client.balance_of_category(&new, &symbol_short!("sports")), 45);
+    assert_eq!(client.total_supply(), 145);
+    assert_eq!(client.migrated_to(&old), Some(new));
+}
+
+#[test]
+#[should_panic(expected = "student_already_migrated")]
+fn test_migrate_student_only_once() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, admin) = setup(&env);
+    let old = Address::generate(&env);
+
+    client.migrate_student(&admin, &old, &Address::generate(&env));
+    client.migrate_student(&admin, &old, &Address::generate(&env));
 }
@@ -0,0 +1,211 @@
+#![no_std]
+//! Control de acceso por roles compartido por los contratos de la plataforma.
+//!
+//! Cada tarea privilegiada se asocia a un `Role` y puede delegarse a una clave
+//! distinta: acuñar puntos (`Minter`), emitir badges (`Reviewer`), registrar canjes
+//! (`Partner`), pausar (`Pauser`) o proponer upgrades (`Upgrader`). Cada rol tiene un
+//! rol administrador (por defecto `Admin`) que es quien puede otorgarlo y revocarlo.
+//!
+//! El rol `Admin` tiene un único titular y solo cambia mediante la acción `SetAdmin`
+//! del timelock (ver `transfer_admin`), para que no pueda rotarse al instante.
+//!
+//! Los contratos exponen las funciones públicas implementando los traits
+//! `AccessControl` y `Pausable` con `#[contractimpl(contracttrait)]`.
+use soroban_sdk::{contracttrait, contracttype, symbol_short, Address, Env};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    Admin,
+    Minter,
+    Reviewer,
+    Partner,
+    Pauser,
+    Upgrader,
+}
+
+const ALL_ROLES: [Role; 6] = [
+    Role::Admin,
+    Role::Minter,
+    Role::Reviewer,
+    Role::Partner,
+    Role::Pauser,
+    Role::Upgrader,
+];
+
+#[contracttype]
+#[derive(Clone)]
+enum AccessKey {
+    HasRole(Role, Address),
+    RoleAdmin(Role),
+    Paused,
+}
+
+// ── Roles ─────────────────────────────────────────────────────────────────────
+
+/// Otorga todos los roles al admin inicial. Se llama una vez al inicializar el
+/// contrato; a partir de ahí el admin reparte cada rol a quien corresponda.
+pub fn init(env: &Env, admin: &Address) {
+    for role in ALL_ROLES {
+        set_role(env, role, admin, true);
+    }
+}
+
+pub fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&AccessKey::HasRole(role, account.clone()))
+        .unwrap_or(false)
+}
+
+/// Exige la firma de `account` y que tenga `role`.
+pub fn require_role(env: &Env, account: &Address, role: Role) {
+    account.require_auth();
+    if !has_role(env, role, account) {
+        panic!("missing_role");
+    }
+}
+
+/// Rol cuyos titulares pueden otorgar y revocar `role`.
+pub fn role_admin(env: &Env, role: Role) -> Role {
+    env.storage()
+        .instance()
+        .get(&AccessKey::RoleAdmin(role))
+        .unwrap_or(Role::Admin)
+}
+
+/// Traspasa a `new_admin` todos los roles que `old_admin` conserva (`Admin` y los que
+/// no haya repartido o renunciado). Solo debe llamarse al ejecutar una acción
+/// `SetAdmin` del timelock.
+#[allow(deprecated)]
+pub fn transfer_admin(env: &Env, old_admin: &Address, new_admin: &Address) {
+    for role in ALL_ROLES {
+        if !has_role(env, role, old_admin) {
+            continue;
+        }
+        set_role(env, role, old_admin, false);
+        set_role(env, role, new_admin, true);
+        env.events().publish(
+            (symbol_short!("role_rvk"), role, old_admin.clone()),
+            new_admin.clone(),
+        );
+        env.events().publish(
+            (symbol_short!("role_grnt"), role, new_admin.clone()),
+            old_admin.clone(),
+        );
+    }
+}
+
+fn set_role(env: &Env, role: Role, account: &Address, granted: bool) {
+    let key = AccessKey::HasRole(role, account.clone());
+    if granted {
+        env.storage().persistent().set(&key, &true);
+        xpuni_common::extend_persistent(env, &key);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+fn require_role_admin(env: &Env, caller: &Address, role: Role) {
+    if role == Role::Admin {
+        panic!("admin_role_is_timelocked");
+    }
+    require_role(env, caller, role_admin(env, role));
+}
+
+// ── Pausa ─────────────────────────────────────────────────────────────────────
+
+pub fn paused(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&AccessKey::Paused)
+        .unwrap_or(false)
+}
+
+pub fn require_not_paused(env: &Env) {
+    if paused(env) {
+        panic!("contract_paused");
+    }
+}
+
+// ── Contract interfaces ───────────────────────────────────────────────────────
+
+#[contracttrait]
+pub trait AccessControl {
+    /// Indica si `account` tiene `role`.
+    fn has_role(env: &Env, role: Role, account: Address) -> bool {
+        has_role(env, role, &account)
+    }
+
+    /// Retorna el rol que administra `role`.
+    fn role_admin(env: &Env, role: Role) -> Role {
+        role_admin(env, role)
+    }
+
+    /// Otorga `role` a `account`. `caller` debe tener el rol administrador de `role`.
+    #[allow(deprecated)]
+    fn grant_role(env: &Env, caller: Address, role: Role, account: Address) {
+        require_role_admin(env, &caller, role);
+        set_role(env, role, &account, true);
+        env.events()
+            .publish((symbol_short!("role_grnt"), role, account), caller);
+    }
+
+    /// Revoca `role` a `account`. `caller` debe tener el rol administrador de `role`.
+    #[allow(deprecated)]
+    fn revoke_role(env: &Env, caller: Address, role: Role, account: Address) {
+        require_role_admin(env, &caller, role);
+        set_role(env, role, &account, false);
+        env.events()
+            .publish((symbol_short!("role_rvk"), role, account), caller);
+    }
+
+    /// `account` renuncia a uno de sus propios roles.
+    #[allow(deprecated)]
+    fn renounce_role(env: &Env, account: Address, role: Role) {
+        if role == Role::Admin {
+            panic!("admin_role_is_timelocked");
+        }
+        require_role(env, &account, role);
+        set_role(env, role, &account, false);
+        env.events()
+            .publish((symbol_short!("role_rvk"), role, account.clone()), account);
+    }
+
+    /// Cambia el rol administrador de `role`. Solo un `Admin`.
+    #[allow(deprecated)]
+    fn set_role_admin(env: &Env, caller: Address, role: Role, admin_role: Role) {
+        if role == Role::Admin {
+            panic!("admin_role_is_timelocked");
+        }
+        require_role(env, &caller, Role::Admin);
+        env.storage()
+            .instance()
+            .set(&AccessKey::RoleAdmin(role), &admin_role);
+        env.events()
+            .publish((symbol_short!("role_adm"), role), admin_role);
+    }
+}
+
+#[contracttrait]
+pub trait Pausable {
+    fn paused(env: &Env) -> bool {
+        paused(env)
+    }
+
+    /// Pausa las operaciones mutantes del contrato. Requiere el rol `Pauser`.
+    #[allow(deprecated)]
+    fn pause(env: &Env, caller: Address) {
+        require_role(env, &caller, Role::Pauser);
+        env.storage().instance().set(&AccessKey::Paused, &true);
+        env.events().publish((symbol_short!("paused"),), caller);
+    }
+
+    /// Reanuda las operaciones mutantes. Requiere el rol `Pauser`.
+    #[allow(deprecated)]
+    fn unpause(env: &Env, caller: Address) {
+        require_role(env, &caller, Role::Pauser);
+        env.storage().instance().set(&AccessKey::Paused, &false);
+        env.events().publish((symbol_short!("unpaused"),), caller);
+    }
+}
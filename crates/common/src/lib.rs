@@ -0,0 +1,342 @@
+#![no_std]
+//! Primitivas compartidas por los contratos de la plataforma: admin, TTL de
+//! almacenamiento persistente, modo de almacenamiento, migración de estudiantes,
+//! borrado de datos personales y un registro append-only con índice paginado por dueño.
+use soroban_sdk::{
+    contracttype, xdr::ToXdr, Address, BytesN, Env, IntoVal, String, Symbol, TryFromVal,
+    Val, Vec,
+};
+
+/// TTL (umbral y extensión) de las entradas persistentes: ~10M ledgers ≈ varios años.
+pub const PERSISTENT_TTL: u32 = 10_000_000;
+
+#[contracttype]
+#[derive(Clone)]
+enum CommonKey {
+    Admin,
+    /// Dirección anterior de un estudiante → dirección nueva.
+    MigratedTo(Address),
+    StorageMode,
+}
+
+// ── Admin ─────────────────────────────────────────────────────────────────────
+
+pub fn admin(env: &Env) -> Address {
+    env.storage().instance().get(&CommonKey::Admin).unwrap()
+}
+
+/// Guarda el admin del contrato. Se llama desde el constructor y al ejecutar
+/// `SetAdmin` del timelock.
+pub fn set_admin(env: &Env, new_admin: &Address) {
+    env.storage().instance().set(&CommonKey::Admin, new_admin);
+}
+
+// ── TTL ───────────────────────────────────────────────────────────────────────
+
+/// Extiende una entrada persistente a `PERSISTENT_TTL`.
+pub fn extend_persistent<K>(env: &Env, key: &K)
+where
+    K: IntoVal<Env, Val>,
+{
+    env.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_TTL, PERSISTENT_TTL);
+}
+
+// ── Modo de almacenamiento ────────────────────────────────────────────────────
+
+/// Cómo guarda un contrato los campos de texto libre de cada elemento. Se elige en el
+/// constructor y no cambia.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StorageMode {
+    /// Los campos se guardan completos on-chain.
+    Full,
+    /// Solo se guarda su `content_hash`; los campos completos se publican una vez en el
+    /// evento de emisión. Ahorra la renta de almacenamiento persistente.
+    Compact,
+}
+
+pub fn storage_mode(env: &Env) -> StorageMode {
+    env.storage()
+        .instance()
+        .get(&CommonKey::StorageMode)
+        .unwrap_or(StorageMode::Full)
+}
+
+/// Guarda el modo de almacenamiento. Solo se llama desde el constructor.
+pub fn set_storage_mode(env: &Env, mode: StorageMode) {
+    env.storage().instance().set(&CommonKey::StorageMode, &mode);
+}
+
+/// SHA-256 del XDR de `content`: el hash que guarda el modo `Compact`.
+pub fn content_hash<T>(env: &Env, content: &T) -> BytesN<32>
+where
+    T: IntoVal<Env, Val>,
+{
+    let content: Val = content.into_val(env);
+    env.crypto().sha256(&content.to_xdr(env)).to_bytes()
+}
+
+// ── Migración de estudiantes ──────────────────────────────────────────────────
+
+/// Registra que el estudiante `old` perdió su wallet y pasa a `new`. Cada dirección
+/// se migra una sola vez y `new` no puede ser una dirección ya migrada.
+pub fn record_migration(env: &Env, old: &Address, new: &Address) {
+    if old == new {
+        panic!("same_address");
+    }
+    if migrated_to(env, old).is_some() || migrated_to(env, new).is_some() {
+        panic!("student_already_migrated");
+    }
+    let key = CommonKey::MigratedTo(old.clone());
+    env.storage().persistent().set(&key, new);
+    extend_persistent(env, &key);
+}
+
+/// Dirección a la que se migró `old`, si fue migrada.
+pub fn migrated_to(env: &Env, old: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&CommonKey::MigratedTo(old.clone()))
+}
+
+// ── Borrado de datos personales ───────────────────────────────────────────────
+
+/// Texto que reemplaza un campo libre borrado a pedido del estudiante.
+pub const REDACTED: &str = "[redacted]";
+
+/// Autoriza borrar datos personales de `student`: lo firma el admin, o el propio
+/// estudiante junto con el admin.
+pub fn require_redaction_auth(env: &Env, caller: &Address, student: &Address) {
+    caller.require_auth();
+    let admin = admin(env);
+    if *caller == admin {
+        return;
+    }
+    if caller != student {
+        panic!("not_authorized");
+    }
+    admin.require_auth();
+}
+
+/// Reemplaza `text` por `REDACTED` y retorna el SHA-256 del texto original, que el
+/// contrato guarda para poder comprobar una copia fuera de la cadena.
+pub fn redact(env: &Env, text: &mut String) -> BytesN<32> {
+    let hash = env.crypto().sha256(&text.to_bytes()).to_bytes();
+    *text = String::from_str(env, REDACTED);
+    hash
+}
+
+// ── Índice paginado ───────────────────────────────────────────────────────────
+
+/// Máximo de elementos que retorna una página.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Lista de IDs que crece sin límite, guardada como un contador por clave más una
+/// entrada persistente por posición: `(ns, key) → len` y `(ns, key, n) → id`.
+/// Agregar un ID escribe dos entradas pequeñas sin importar cuántas haya ya, y se
+/// lee por páginas.
+pub struct Index {
+    ns: Symbol,
+}
+
+impl Index {
+    pub const fn new(ns: Symbol) -> Self {
+        Index { ns }
+    }
+
+    /// Cantidad de IDs bajo `key`.
+    pub fn len<K>(&self, env: &Env, key: &K) -> u32
+    where
+        K: Clone,
+        Val: TryFromVal<Env, K>,
+    {
+        env.storage()
+            .persistent()
+            .get(&(self.ns.clone(), key.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Agrega `id` al final de la lista de `key`. Retorna su posición.
+    pub fn push<K>(&self, env: &Env, key: &K, id: u64) -> u32
+    where
+        K: Clone,
+        Val: TryFromVal<Env, K>,
+    {
+        let n = self.len(env, key);
+
+        let item_key = (self.ns.clone(), key.clone(), n);
+        env.storage().persistent().set(&item_key, &id);
+        extend_persistent(env, &item_key);
+
+        let len_key = (self.ns.clone(), key.clone());
+        env.storage().persistent().set(&len_key, &(n + 1));
+        extend_persistent(env, &len_key);
+
+        n
+    }
+
+    /// ID en la posición `n` de la lista de `key`.
+    pub fn get<K>(&self, env: &Env, key: &K, n: u32) -> Option<u64>
+    where
+        K: Clone,
+        Val: TryFromVal<Env, K>,
+    {
+        env.storage()
+            .persistent()
+            .get(&(self.ns.clone(), key.clone(), n))
+    }
+
+    /// Mueve todos los IDs de `from` al final de la lista de `to`, en orden, y deja
+    /// `from` vacía. Retorna los IDs movidos.
+    pub fn move_all<K>(&self, env: &Env, from: &K, to: &K) -> Vec<u64>
+    where
+        K: Clone,
+        Val: TryFromVal<Env, K>,
+    {
+        let mut moved = Vec::new(env);
+        for n in 0..self.len(env, from) {
+            let item_key = (self.ns.clone(), from.clone(), n);
+            if let Some(id) = env.storage().persistent().get(&item_key) {
+                self.push(env, to, id);
+                moved.push_back(id);
+            }
+            env.storage().persistent().remove(&item_key);
+        }
+        env.storage()
+            .persistent()
+            .remove(&(self.ns.clone(), from.clone()));
+        moved
+    }
+
+    /// Hasta `limit` IDs (máximo `MAX_PAGE_SIZE`) a partir de la posición `start`.
+    pub fn page<K>(&self, env: &Env, key: &K, start: u32, limit: u32) -> Vec<u64>
+    where
+        K: Clone,
+        Val: TryFromVal<Env, K>,
+    {
+        let len = self.len(env, key);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+        let mut result = Vec::new(env);
+        for n in start..end {
+            if let Some(id) = self.get(env, key, n) {
+                result.push_back(id);
+            }
+        }
+        result
+    }
+}
+
+// ── Registro append-only ──────────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone)]
+enum RegistryKey {
+    Count(Symbol),
+    Item(Symbol, u64),
+}
+
+/// Registro append-only de elementos con IDs secuenciales desde 0 y un índice
+/// paginado de IDs por dueño. `ns` separa registros distintos dentro del mismo
+/// contrato.
+pub struct Registry {
+    ns: Symbol,
+}
+
+impl Registry {
+    pub const fn new(ns: Symbol) -> Self {
+        Registry { ns }
+    }
+
+    /// Total de elementos registrados (y por tanto el próximo ID).
+    pub fn count(&self, env: &Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&RegistryKey::Count(self.ns.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Registra el elemento que construye `build` con el ID asignado y lo agrega al
+    /// índice de `owner`. Retorna el ID.
+    pub fn append<T, F>(&self, env: &Env, owner: &Address, build: F) -> u64
+    where
+        T: IntoVal<Env, Val>,
+        F: FnOnce(u64) -> T,
+    {
+        let id = self.insert(env, build);
+        self.add_to_owner(env, owner, id);
+        id
+    }
+
+    /// Como `append`, pero sin agregar el elemento a ningún índice por dueño; el
+    /// contrato lo hace después con `add_to_owner` (p. ej. cuando el dueño lo acepta).
+    pub fn insert<T, F>(&self, env: &Env, build: F) -> u64
+    where
+        T: IntoVal<Env, Val>,
+        F: FnOnce(u64) -> T,
+    {
+        let id = self.count(env);
+
+        let item_key = RegistryKey::Item(self.ns.clone(), id);
+        env.storage().persistent().set(&item_key, &build(id));
+        extend_persistent(env, &item_key);
+
+        env.storage()
+            .instance()
+            .set(&RegistryKey::Count(self.ns.clone()), &(id + 1));
+
+        id
+    }
+
+    /// Agrega un elemento ya registrado al índice de `owner`.
+    pub fn add_to_owner(&self, env: &Env, owner: &Address, id: u64) {
+        self.owners().push(env, owner, id);
+    }
+
+    /// Borra un elemento que nunca llegó a ningún índice por dueño. Su ID no se reusa.
+    pub fn remove(&self, env: &Env, id: u64) {
+        env.storage()
+            .persistent()
+            .remove(&RegistryKey::Item(self.ns.clone(), id));
+    }
+
+    pub fn get<T>(&self, env: &Env, id: u64) -> Option<T>
+    where
+        T: TryFromVal<Env, Val>,
+    {
+        env.storage()
+            .persistent()
+            .get(&RegistryKey::Item(self.ns.clone(), id))
+    }
+
+    /// Reemplaza un elemento ya registrado. No toca el índice por dueño.
+    pub fn replace<T>(&self, env: &Env, id: u64, item: &T)
+    where
+        T: IntoVal<Env, Val>,
+    {
+        let item_key = RegistryKey::Item(self.ns.clone(), id);
+        env.storage().persistent().set(&item_key, item);
+        extend_persistent(env, &item_key);
+    }
+
+    /// Mueve todos los IDs de `old` al índice de `new`. Retorna los IDs movidos; cada
+    /// contrato actualiza el dueño guardado en sus elementos.
+    pub fn move_owner(&self, env: &Env, old: &Address, new: &Address) -> Vec<u64> {
+        self.owners().move_all(env, old, new)
+    }
+
+    /// Cantidad de elementos registrados para `owner`.
+    pub fn count_of(&self, env: &Env, owner: &Address) -> u32 {
+        self.owners().len(env, owner)
+    }
+
+    /// Página de IDs registrados para `owner`, en orden de registro.
+    pub fn ids_of(&self, env: &Env, owner: &Address, start: u32, limit: u32) -> Vec<u64> {
+        self.owners().page(env, owner, start, limit)
+    }
+
+    fn owners(&self) -> Index {
+        Index::new(self.ns.clone())
+    }
+}
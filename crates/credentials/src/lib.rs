@@ -0,0 +1,246 @@
+//! Exporta los badges de `achievement_badges` como credenciales Open Badges 3.0 (W3C
+//! Verifiable Credentials en JSON-LD) y verifica credenciales contra el registro
+//! on-chain.
+//!
+//! Corre fuera de la cadena (backend, herramientas de verificación): recibe el badge ya
+//! decodificado del storage o de los eventos del contrato, con las direcciones como
+//! strkeys (`G...`, `C...`) y los hashes en hex. Las credenciales no llevan firma: su
+//! raíz de confianza es el ancla on-chain (red, contract ID y badge_id), con la que
+//! cualquiera puede volver a leer el registro y comprobarla con `verify`.
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+
+pub const VC_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const OB_CONTEXT: &str = "https://purl.imsglobal.org/spec/ob/v3p0/context-3.0.3.json";
+/// Vocabulario de los términos `xpuni:*` del ancla on-chain.
+pub const XPUNI_VOCAB: &str = "https://xpuni.app/ns#";
+
+// ── Registro on-chain ─────────────────────────────────────────────────────────
+
+/// Dónde vive el badge: red de Stellar (`pubnet`, `testnet`) y contrato
+/// `AchievementBadges`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub network: String,
+    pub contract_id: String,
+}
+
+/// Perfil de la institución que emite, tal como se publica en la credencial.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issuer {
+    /// URL del perfil público de la institución.
+    pub id: String,
+    pub name: String,
+}
+
+/// `BadgeData` decodificado, junto con su estado (`is_valid`, `valid_until`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainBadge {
+    pub badge_id: u64,
+    #[serde(default)]
+    pub class_id: Option<u64>,
+    pub student: String,
+    pub activity_id: u64,
+    pub activity_title: String,
+    pub image_uri: String,
+    pub reviewer: String,
+    pub points_awarded: i128,
+    /// Timestamp Unix del ledger al emitirse.
+    pub issued_at: u64,
+    /// SHA-256 de la evidencia, en hex.
+    pub description_hash: String,
+    #[serde(default)]
+    pub revoked: bool,
+    #[serde(default)]
+    pub valid_until: Option<u64>,
+}
+
+/// `BadgeClass` decodificada.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainClass {
+    pub class_id: u64,
+    pub title: String,
+    pub image_uri: String,
+    /// SHA-256 de los criterios de obtención, en hex.
+    pub criteria_hash: String,
+    pub default_points: i128,
+}
+
+// ── Exportación ───────────────────────────────────────────────────────────────
+
+/// Construye la `OpenBadgeCredential` de un badge. Si el badge se emitió desde una
+/// clase, el `Achievement` es la clase (compartido por todos sus badges); si no, la
+/// actividad. `class` debe ser la clase `badge.class_id`; `verify` lo comprueba.
+pub fn to_credential(
+    anchor: &Anchor,
+    issuer: &Issuer,
+    badge: &OnChainBadge,
+    class: Option<&OnChainClass>,
+) -> Value {
+    let base = format!("urn:stellar:{}:{}", anchor.network, anchor.contract_id);
+
+    let achievement = match class {
+        Some(class) => json!({
+            "id": format!("{base}:class:{}", class.class_id),
+            "type": ["Achievement"],
+            "name": class.title,
+            "criteria": { "id": format!("urn:sha256:{}", class.criteria_hash) },
+            "image": { "id": class.image_uri, "type": "Image" },
+        }),
+        None => json!({
+            "id": format!("{base}:activity:{}", badge.activity_id),
+            "type": ["Achievement"],
+            "name": badge.activity_title,
+            "criteria": { "narrative": badge.activity_title },
+            "image": { "id": badge.image_uri, "type": "Image" },
+        }),
+    };
+
+    let mut credential = json!({
+        "@context": [VC_CONTEXT, OB_CONTEXT, { "xpuni": XPUNI_VOCAB }],
+        "id": format!("{base}:badge:{}", badge.badge_id),
+        "type": ["VerifiableCredential", "OpenBadgeCredential"],
+        "issuer": { "id": issuer.id, "type": ["Profile"], "name": issuer.name },
+        "validFrom": format_timestamp(badge.issued_at),
+        "name": achievement["name"],
+        "credentialSubject": {
+            "id": format!("did:pkh:stellar:{}:{}", anchor.network, badge.student),
+            "type": ["AchievementSubject"],
+            "achievement": achievement,
+        },
+        "evidence": [{
+            "id": format!("urn:sha256:{}", badge.description_hash),
+            "type": ["Evidence"],
+        }],
+        "xpuni:anchor": {
+            "xpuni:network": anchor.network,
+            "xpuni:contractId": anchor.contract_id,
+            "xpuni:badgeId": badge.badge_id,
+            "xpuni:activityId": badge.activity_id,
+            "xpuni:reviewer": badge.reviewer,
+            // Como texto: `i128` no cabe en un número JSON.
+            "xpuni:pointsAwarded": badge.points_awarded.to_string(),
+        },
+    });
+    if let Some(valid_until) = badge.valid_until {
+        credential["validUntil"] = json!(format_timestamp(valid_until));
+    }
+    credential
+}
+
+// ── Verificación ──────────────────────────────────────────────────────────────
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// El badge fue revocado on-chain.
+    Revoked,
+    /// El badge venció on-chain.
+    Expired,
+    /// El campo (JSON pointer) falta, sobra o no coincide con el registro on-chain.
+    Mismatch(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Revoked => write!(f, "badge revocado on-chain"),
+            VerifyError::Expired => write!(f, "badge vencido on-chain"),
+            VerifyError::Mismatch(field) => write!(f, "`{field}` no coincide con on-chain"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Comprueba una credencial contra el registro on-chain decodificado: que `class` sea
+/// la clase del badge, que el badge siga vigente a `now` (timestamp Unix) y que el
+/// documento entero sea el que produciría `to_credential`, sin campos de más ni de
+/// menos.
+pub fn verify(
+    credential: &Value,
+    anchor: &Anchor,
+    issuer: &Issuer,
+    badge: &OnChainBadge,
+    class: Option<&OnChainClass>,
+    now: u64,
+) -> Result<(), VerifyError> {
+    if class.map(|class| class.class_id) != badge.class_id {
+        return Err(VerifyError::Mismatch("/credentialSubject/achievement/id".into()));
+    }
+    if badge.revoked {
+        return Err(VerifyError::Revoked);
+    }
+    if badge.valid_until.is_some_and(|valid_until| now > valid_until) {
+        return Err(VerifyError::Expired);
+    }
+
+    let expected = to_credential(anchor, issuer, badge, class);
+    match first_difference(credential, &expected, "") {
+        Some(field) => Err(VerifyError::Mismatch(field)),
+        None => Ok(()),
+    }
+}
+
+// ── Helpers internos ──────────────────────────────────────────────────────────
+
+/// JSON pointer del primer valor en que `actual` difiere de `expected`, bajo `pointer`.
+fn first_difference(actual: &Value, expected: &Value, pointer: &str) -> Option<String> {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, value) in expected {
+                let field = format!("{pointer}/{}", escape_pointer(key));
+                match actual.get(key) {
+                    Some(found) => {
+                        if let Some(diff) = first_difference(found, value, &field) {
+                            return Some(diff);
+                        }
+                    }
+                    None => return Some(field),
+                }
+            }
+            actual
+                .keys()
+                .find(|key| !expected.contains_key(*key))
+                .map(|key| format!("{pointer}/{}", escape_pointer(key)))
+        }
+        (Value::Array(actual), Value::Array(expected)) if actual.len() == expected.len() => {
+            actual.iter().zip(expected).enumerate().find_map(|(i, (found, value))| {
+                first_difference(found, value, &format!("{pointer}/{i}"))
+            })
+        }
+        _ if actual == expected => None,
+        _ => Some(pointer.to_string()),
+    }
+}
+
+/// Escapa una clave para usarla en un JSON pointer (RFC 6901).
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Timestamp Unix → fecha RFC 3339 en UTC (`2025-01-01T00:00:00Z`).
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Días desde 1970-01-01 → fecha civil (algoritmo de H. Hinnant).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+mod test;
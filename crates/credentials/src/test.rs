@@ -0,0 +1,137 @@
+#![cfg(test)]
+use super::*;
+
+#[derive(Deserialize)]
+struct Record {
+    anchor: Anchor,
+    issuer: Issuer,
+    badge: OnChainBadge,
+    class: Option<OnChainClass>,
+}
+
+fn setup() -> (Record, Value) {
+    let record = serde_json::from_str(include_str!("../fixtures/badge_record.json")).unwrap();
+    let credential = serde_json::from_str(include_str!("../fixtures/credential.json")).unwrap();
+    (record, credential)
+}
+
+/// Un momento dentro de la vigencia del badge del fixture.
+const NOW: u64 = 1_750_000_000;
+
+#[test]
+fn test_export_matches_fixture() {
+    let (r, credential) = setup();
+    let exported = to_credential(&r.anchor, &r.issuer, &r.badge, r.class.as_ref());
+    assert_eq!(exported, credential);
+}
+
+#[test]
+fn test_export_without_class_uses_activity() {
+    let (r, _) = setup();
+    let badge = OnChainBadge {
+        class_id: None,
+        activity_title: "Hackathon 2025".into(),
+        image_uri: "ipfs://bafybeihackathon".into(),
+        valid_until: None,
+        ..r.badge
+    };
+    let exported = to_credential(&r.anchor, &r.issuer, &badge, None);
+
+    let achievement = &exported["credentialSubject"]["achievement"];
+    assert!(achievement["id"].as_str().unwrap().ends_with(":activity:42"));
+    assert_eq!(achievement["name"], "Hackathon 2025");
+    assert_eq!(achievement["image"]["id"], "ipfs://bafybeihackathon");
+    assert!(exported.get("validUntil").is_none());
+}
+
+#[test]
+fn test_verify_fixture() {
+    let (r, credential) = setup();
+    let result = verify(&credential, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), NOW);
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn test_verify_tampered_credential() {
+    let (r, mut credential) = setup();
+    credential["credentialSubject"]["achievement"]["name"] = json!("Hackathon Winner");
+
+    let result = verify(&credential, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), NOW);
+    let expected = Err(VerifyError::Mismatch("/credentialSubject/achievement/name".into()));
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_verify_tampered_top_level_fields() {
+    let (r, credential) = setup();
+
+    for field in credential.as_object().unwrap().keys() {
+        let mut tampered = credential.clone();
+        tampered[field] = json!("tampered");
+        let result = verify(&tampered, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), NOW);
+        assert_eq!(result, Err(VerifyError::Mismatch(format!("/{field}"))), "{field}");
+    }
+}
+
+#[test]
+fn test_verify_rejects_missing_and_extra_fields() {
+    let (r, credential) = setup();
+
+    let mut missing = credential.clone();
+    missing.as_object_mut().unwrap().remove("validUntil");
+    let result = verify(&missing, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), NOW);
+    assert_eq!(result, Err(VerifyError::Mismatch("/validUntil".into())));
+
+    let mut extra = credential;
+    extra["issuer"]["url"] = json!("https://example.com");
+    let result = verify(&extra, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), NOW);
+    assert_eq!(result, Err(VerifyError::Mismatch("/issuer/url".into())));
+}
+
+#[test]
+fn test_verify_other_student() {
+    let (r, credential) = setup();
+    let badge = OnChainBadge { student: "GOTHERSTUDENT".into(), ..r.badge };
+
+    let result = verify(&credential, &r.anchor, &r.issuer, &badge, r.class.as_ref(), NOW);
+    assert_eq!(result, Err(VerifyError::Mismatch("/credentialSubject/id".into())));
+}
+
+#[test]
+fn test_verify_rejects_other_class() {
+    let (r, _) = setup();
+    let other = OnChainClass { class_id: 4, title: "Otra clase".into(), ..r.class.unwrap() };
+    // Aunque la credencial se haya armado con la misma clase equivocada.
+    let credential = to_credential(&r.anchor, &r.issuer, &r.badge, Some(&other));
+
+    let result = verify(&credential, &r.anchor, &r.issuer, &r.badge, Some(&other), NOW);
+    let expected = Err(VerifyError::Mismatch("/credentialSubject/achievement/id".into()));
+    assert_eq!(result, expected);
+    let result = verify(&credential, &r.anchor, &r.issuer, &r.badge, None, NOW);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_verify_revoked_badge() {
+    let (r, credential) = setup();
+    let badge = OnChainBadge { revoked: true, ..r.badge };
+
+    let result = verify(&credential, &r.anchor, &r.issuer, &badge, r.class.as_ref(), NOW);
+    assert_eq!(result, Err(VerifyError::Revoked));
+}
+
+#[test]
+fn test_verify_expired_badge() {
+    let (r, credential) = setup();
+    let now = r.badge.valid_until.unwrap() + 1;
+
+    let result = verify(&credential, &r.anchor, &r.issuer, &r.badge, r.class.as_ref(), now);
+    assert_eq!(result, Err(VerifyError::Expired));
+}
+
+#[test]
+fn test_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+    assert_eq!(format_timestamp(951_782_400 + 3_723), "2000-02-29T01:02:03Z");
+    assert_eq!(format_timestamp(1_767_225_599), "2025-12-31T23:59:59Z");
+}
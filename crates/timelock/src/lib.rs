@@ -0,0 +1,275 @@
+#![no_std]
+//! Timelock compartido por los contratos de la plataforma.
+//!
+//! Las acciones privilegiadas (rotar el admin, cambiar tasas de cambio, actualizar el
+//! WASM) no se aplican en el momento: se encolan con un retardo mínimo en ledgers y
+//! solo pueden ejecutarse cuando ese retardo ha pasado. Mientras tanto el admin puede
+//! cancelarlas, y estudiantes y partners tienen una ventana para reaccionar.
+//!
+//! Los contratos exponen las funciones públicas implementando el trait `Timelocked` con
+//! `#[contractimpl(contracttrait)]`; cada uno solo define en `TimelockHook` cómo aplica
+//! una acción ya ejecutada.
+use soroban_sdk::{contracttrait, contracttype, symbol_short, Address, BytesN, Env, Map, Vec};
+use xpuni_access::Role;
+
+/// Retardo mínimo por defecto: ~1 día con ledgers de 5 segundos.
+pub const DEFAULT_MIN_DELAY: u32 = 17_280;
+
+/// Ledgers que una operación sigue ejecutable después de estar lista (~30 días). Pasada
+/// la ventana vence: ya no puede ejecutarse y su entrada se archiva.
+pub const EXECUTION_WINDOW: u32 = 30 * DEFAULT_MIN_DELAY;
+
+// ── Data structures ───────────────────────────────────────────────────────────
+
+/// Acción privilegiada diferida. Cada contrato acepta solo las que le aplican.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TimelockAction {
+    /// Transfiere el rol de admin a la dirección indicada.
+    SetAdmin(Address),
+    /// Fija la tasa `from → to` (escalada por 1_000_000) en `InstitutionHub`.
+    SetExchangeRate(Address, Address, i128),
+    /// Reemplaza el WASM del contrato por el del hash indicado.
+    UpgradeWasm(BytesN<32>),
+    /// Cambia el retardo mínimo del propio timelock.
+    SetMinDelay(u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedOperation {
+    pub op_id: u64,
+    pub action: TimelockAction,
+    /// Ledger en que se encoló.
+    pub queued_ledger: u32,
+    /// Primer ledger en que puede ejecutarse.
+    pub ready_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum TimelockKey {
+    MinDelay,
+    OpCount,
+    Op(u64),
+    /// Operaciones encoladas, ni ejecutadas ni canceladas: op_id → último ledger en que
+    /// pueden ejecutarse. Guardar el vencimiento aquí permite descartar las vencidas sin
+    /// leer su entrada, que para entonces puede estar archivada. Las vencidas se quitan
+    /// al encolar o sacar otra operación.
+    Pending,
+}
+
+// ── Queue ─────────────────────────────────────────────────────────────────────
+
+/// Retardo mínimo vigente, en ledgers.
+pub fn min_delay(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&TimelockKey::MinDelay)
+        .unwrap_or(DEFAULT_MIN_DELAY)
+}
+
+/// Fija el retardo mínimo. Solo debe llamarse al ejecutar `SetMinDelay`.
+#[allow(deprecated)]
+pub fn set_min_delay(env: &Env, ledgers: u32) {
+    env.storage().instance().set(&TimelockKey::MinDelay, &ledgers);
+    env.events().publish((symbol_short!("tl_delay"),), ledgers);
+}
+
+/// Encola `action` para ejecutarse dentro de `delay` ledgers. Retorna el op_id.
+#[allow(deprecated)]
+pub fn queue(env: &Env, action: TimelockAction, delay: u32) -> u64 {
+    if delay < min_delay(env) {
+        panic!("delay_below_minimum");
+    }
+
+    let op_id: u64 = env
+        .storage()
+        .instance()
+        .get(&TimelockKey::OpCount)
+        .unwrap_or(0);
+    let queued_ledger = env.ledger().sequence();
+    let op = QueuedOperation {
+        op_id,
+        action: action.clone(),
+        queued_ledger,
+        ready_ledger: queued_ledger.saturating_add(delay),
+    };
+
+    // La operación, la cola y el propio contrato deben sobrevivir al retardo más la
+    // ventana de ejecución.
+    let ttl = delay.saturating_add(EXECUTION_WINDOW);
+    env.storage().persistent().set(&TimelockKey::Op(op_id), &op);
+    env.storage()
+        .persistent()
+        .extend_ttl(&TimelockKey::Op(op_id), ttl, ttl);
+    env.storage().instance().extend_ttl(ttl, ttl);
+
+    let mut pending = live_pending(env);
+    pending.set(op_id, expiry_ledger(&op));
+    store_pending(env, &pending);
+    env.storage()
+        .persistent()
+        .extend_ttl(&TimelockKey::Pending, ttl, ttl);
+    env.storage()
+        .instance()
+        .set(&TimelockKey::OpCount, &(op_id + 1));
+
+    env.events()
+        .publish((symbol_short!("tl_queue"), op_id), (action, op.ready_ledger));
+
+    op_id
+}
+
+/// Saca de la cola una operación lista y retorna su acción para que el contrato la
+/// aplique. Falla si no existe, si su retardo todavía no se ha cumplido o si ya pasó su
+/// ventana de ejecución.
+#[allow(deprecated)]
+pub fn execute(env: &Env, op_id: u64) -> TimelockAction {
+    let op = get(env, op_id);
+    let now = env.ledger().sequence();
+    if now < op.ready_ledger {
+        panic!("operation_not_ready");
+    }
+    if now > expiry_ledger(&op) {
+        panic!("operation_expired");
+    }
+    remove(env, op_id);
+
+    env.events()
+        .publish((symbol_short!("tl_exec"), op_id), op.action.clone());
+
+    op.action
+}
+
+/// Descarta una operación pendiente.
+#[allow(deprecated)]
+pub fn cancel(env: &Env, op_id: u64) {
+    let op = get(env, op_id);
+    remove(env, op_id);
+
+    env.events()
+        .publish((symbol_short!("tl_cancel"), op_id), op.action);
+}
+
+/// Retorna una operación pendiente por su ID.
+pub fn get(env: &Env, op_id: u64) -> QueuedOperation {
+    env.storage()
+        .persistent()
+        .get(&TimelockKey::Op(op_id))
+        .unwrap_or_else(|| panic!("operation_not_found"))
+}
+
+/// Retorna las operaciones pendientes y todavía ejecutables, en orden de encolado. Solo
+/// lee: las vencidas se omiten sin tocar la cola.
+pub fn pending(env: &Env) -> Vec<QueuedOperation> {
+    let now = env.ledger().sequence();
+    let mut result = Vec::new(env);
+    for (op_id, expiry) in stored_pending(env).iter() {
+        if now <= expiry {
+            result.push_back(get(env, op_id));
+        }
+    }
+    result
+}
+
+// ── Contract interface ────────────────────────────────────────────────────────
+
+/// Parte propia de cada contrato: cómo aplica las acciones que dependen de su storage.
+/// Es un trait aparte para que no se exporte como función del contrato.
+pub trait TimelockHook {
+    /// Si el contrato acepta `SetExchangeRate`. Los que no la rechazan ya al encolar.
+    const EXCHANGE_RATES: bool = false;
+
+    /// Aplica `SetAdmin` y, si `EXCHANGE_RATES`, `SetExchangeRate`. `UpgradeWasm` y
+    /// `SetMinDelay` los aplica `execute_action` sin pasar por aquí.
+    fn apply_action(env: &Env, action: TimelockAction);
+}
+
+#[contracttrait]
+pub trait Timelocked: TimelockHook {
+    /// Encola una acción privilegiada para ejecutarse dentro de `delay` ledgers.
+    /// `UpgradeWasm` requiere el rol `Upgrader`; el resto, `Admin`. Retorna el op_id.
+    fn queue_action(env: &Env, caller: Address, action: TimelockAction, delay: u32) -> u64 {
+        let role = match action {
+            TimelockAction::UpgradeWasm(_) => Role::Upgrader,
+            TimelockAction::SetExchangeRate(..) if !Self::EXCHANGE_RATES => {
+                panic!("unsupported_action")
+            }
+            TimelockAction::SetExchangeRate(_, _, rate) if rate <= 0 => {
+                panic!("rate_must_be_positive")
+            }
+            _ => Role::Admin,
+        };
+        xpuni_access::require_role(env, &caller, role);
+        queue(env, action, delay)
+    }
+
+    /// Aplica una acción encolada cuyo retardo ya se cumplió. Cualquiera puede llamarla.
+    fn execute_action(env: &Env, op_id: u64) {
+        match execute(env, op_id) {
+            TimelockAction::UpgradeWasm(wasm_hash) => {
+                env.deployer().update_current_contract_wasm(wasm_hash);
+            }
+            TimelockAction::SetMinDelay(ledgers) => set_min_delay(env, ledgers),
+            action => Self::apply_action(env, action),
+        }
+    }
+
+    /// Cancela una acción encolada. Requiere el rol `Admin`.
+    fn cancel_action(env: &Env, caller: Address, op_id: u64) {
+        xpuni_access::require_role(env, &caller, Role::Admin);
+        cancel(env, op_id);
+    }
+
+    /// Retorna las acciones encoladas pendientes de ejecutar o cancelar.
+    fn pending_actions(env: &Env) -> Vec<QueuedOperation> {
+        pending(env)
+    }
+
+    /// Retorna el retardo mínimo del timelock, en ledgers.
+    fn min_delay(env: &Env) -> u32 {
+        min_delay(env)
+    }
+}
+
+// ── Helpers internos ──────────────────────────────────────────────────────────
+
+fn expiry_ledger(op: &QueuedOperation) -> u32 {
+    op.ready_ledger.saturating_add(EXECUTION_WINDOW)
+}
+
+fn stored_pending(env: &Env) -> Map<u64, u32> {
+    env.storage()
+        .persistent()
+        .get(&TimelockKey::Pending)
+        .unwrap_or_else(|| Map::new(env))
+}
+
+fn store_pending(env: &Env, pending: &Map<u64, u32>) {
+    if pending.is_empty() {
+        env.storage().persistent().remove(&TimelockKey::Pending);
+    } else {
+        env.storage().persistent().set(&TimelockKey::Pending, pending);
+    }
+}
+
+/// Cola pendiente sin las operaciones vencidas.
+fn live_pending(env: &Env) -> Map<u64, u32> {
+    let now = env.ledger().sequence();
+    let mut pending = stored_pending(env);
+    for (op_id, expiry) in pending.clone().iter() {
+        if now > expiry {
+            pending.remove(op_id);
+        }
+    }
+    pending
+}
+
+fn remove(env: &Env, op_id: u64) {
+    env.storage().persistent().remove(&TimelockKey::Op(op_id));
+
+    let mut pending = live_pending(env);
+    pending.remove(op_id);
+    store_pending(env, &pending);
+}